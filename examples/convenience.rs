@@ -1,15 +1,12 @@
 //! Example of how to use the convenience functions
 //!
 
-extern crate failure;
 extern crate openslide;
 
+use openslide::OpenSlideError;
 use std::path::Path;
-use failure::Error;
 
-fn basic_usage(
-    filename: &Path
-) -> Result<(), Error> {
+fn basic_usage(filename: &Path) -> Result<(), OpenSlideError> {
     let os = openslide::OpenSlide::new(filename)?;
 
     println!("Num levels: {}", os.get_level_count()?);
@@ -1,17 +1,12 @@
 //! Example of how to use the raw bindings
 //!
 
-extern crate failure;
 extern crate openslide;
 
-use openslide::{bindings, utils};
+use openslide::{bindings, utils, OpenSlideError};
 use std::path::Path;
-use failure::Error;
-
-fn basic_usage(
-    filename: &str
-) -> Result<(), Error> {
 
+fn basic_usage(filename: &str) -> Result<(), OpenSlideError> {
     unsafe {
         let vendor = bindings::detect_vendor(filename)?;
         println!("Vendor: {}", vendor);
@@ -56,9 +51,7 @@ fn basic_usage(
     Ok(())
 }
 
-fn properties(
-    filename: &str
-) -> Result<(), Error> {
+fn properties(filename: &str) -> Result<(), OpenSlideError> {
     let osr = bindings::open(filename)?;
 
     println!("Slide in {} has the following properties:", filename);
@@ -73,10 +66,7 @@ fn properties(
     Ok(())
 }
 
-
-fn associated_images(
-    filename: &str
-) -> Result<(), Error> {
+fn associated_images(filename: &str) -> Result<(), OpenSlideError> {
     let osr = bindings::open(filename)?;
 
     println!("Slide in {} has the following associated images:", filename);
@@ -2,14 +2,14 @@
 //!
 
 extern crate openslide;
-extern crate failure;
 extern crate image;
 #[macro_use] extern crate clap;
 
+use std::error::Error;
 use std::path::Path;
 use std::fs;
 
-use failure::{Error, format_err};
+use openslide::OpenSlideError;
 use clap::{Arg, App, ArgMatches};
 use openslide::OpenSlide;
 
@@ -91,7 +91,7 @@ fn write_region(
     target_height: i32,
     target_width: i32,
     zoom_factor: f32,
-) -> Result<(), Error> {
+) -> Result<(), OpenSlideError> {
 
     let zoom_lvl = os.get_best_level_for_downsample(zoom_factor as f64)?;
     println!("Max number of levels: {}", os.get_level_count()?);
@@ -131,7 +131,7 @@ fn write_region(
     Ok(())
 }
 
-fn main() -> Result<(), Error> {
+fn main() -> Result<(), Box<dyn Error>> {
 
     let matches = get_cli();
 
@@ -141,7 +141,9 @@ fn main() -> Result<(), Error> {
             if filepath.exists() {
                 filepath
             } else {
-                return Err(format_err!("Input file does not exist"))
+                return Err(Box::new(OpenSlideError::NonexistentPath(
+                    filepath.to_path_buf(),
+                )));
             }
         }
         None => unreachable!()
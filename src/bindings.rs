@@ -6,7 +6,7 @@
 //! For a more rust convenient api, use the OpenSlide struct.
 //!
 
-use failure::Error;
+use error::OpenSlideError;
 use libc;
 use std::{self, ffi, str};
 
@@ -65,9 +65,7 @@ extern "C" {
     // Error handling
     // ---------------
 
-    // fn openslide_get_error(
-    //     osr: *const OpenSlideT
-    // ) -> *const libc::c_char;
+    fn openslide_get_error(osr: *const OpenSlideT) -> *const libc::c_char;
 
     // ---------------
     // Properties
@@ -98,6 +96,12 @@ extern "C" {
         name: *const libc::c_char,
         dest: *mut libc::uint32_t,
     ) -> libc::c_void;
+
+    // ---------------
+    // Utility functions
+    // ---------------
+
+    fn openslide_get_version() -> *const libc::c_char;
 }
 
 // ---------------
@@ -105,19 +109,35 @@ extern "C" {
 // ---------------
 
 /// Quickly determine whether a whole slide image is recognized.
-pub fn detect_vendor(filename: &str) -> Result<String, Error> {
+///
+/// Returns an error if the file is not recognized (OpenSlide returns NULL).
+pub fn detect_vendor(filename: &str) -> Result<String, OpenSlideError> {
     let c_filename = ffi::CString::new(filename)?;
     let vendor = unsafe {
         let c_vendor = openslide_detect_vendor(c_filename.as_ptr());
+        if c_vendor.is_null() {
+            return Err(OpenSlideError::OpenFailed(format!(
+                "{} is not recognized by OpenSlide",
+                filename
+            )));
+        }
         ffi::CStr::from_ptr(c_vendor).to_string_lossy().into_owned()
     };
     Ok(vendor)
 }
 
 /// Open a whole slide image.
-pub fn open(filename: &str) -> Result<*const OpenSlideT, Error> {
+///
+/// Returns an error if the file is not recognized (OpenSlide returns NULL).
+pub fn open(filename: &str) -> Result<*const OpenSlideT, OpenSlideError> {
     let c_filename = ffi::CString::new(filename)?;
     let slide = unsafe { openslide_open(c_filename.as_ptr()) };
+    if slide.is_null() {
+        return Err(OpenSlideError::OpenFailed(format!(
+            "{} could not be opened by OpenSlide",
+            filename
+        )));
+    }
     Ok(slide)
 }
 
@@ -127,13 +147,13 @@ pub unsafe fn close(osr: *const OpenSlideT) {
 }
 
 /// Get the number of levels in the whole slide image.
-pub unsafe fn get_level_count(osr: *const OpenSlideT) -> Result<i32, Error> {
+pub unsafe fn get_level_count(osr: *const OpenSlideT) -> Result<i32, OpenSlideError> {
     let num_levels = openslide_get_level_count(osr); // This is unsafe
     Ok(num_levels)
 }
 
 /// Get the dimensions of level 0 (the largest level).
-pub unsafe fn get_level0_dimensions(osr: *const OpenSlideT) -> Result<(i64, i64), Error> {
+pub unsafe fn get_level0_dimensions(osr: *const OpenSlideT) -> Result<(i64, i64), OpenSlideError> {
     let mut width: libc::int64_t = 0;
     let mut height: libc::int64_t = 0;
     openslide_get_level0_dimensions(osr, &mut width, &mut height); // This is unsafe
@@ -141,7 +161,10 @@ pub unsafe fn get_level0_dimensions(osr: *const OpenSlideT) -> Result<(i64, i64)
 }
 
 /// Get the dimensions of a level.
-pub unsafe fn get_level_dimensions(osr: *const OpenSlideT, level: i32) -> Result<(i64, i64), Error> {
+pub unsafe fn get_level_dimensions(
+    osr: *const OpenSlideT,
+    level: i32,
+) -> Result<(i64, i64), OpenSlideError> {
     let mut width: libc::int64_t = 0;
     let mut height: libc::int64_t = 0;
     openslide_get_level_dimensions(osr, level, &mut width, &mut height); // This is unsafe
@@ -149,7 +172,10 @@ pub unsafe fn get_level_dimensions(osr: *const OpenSlideT, level: i32) -> Result
 }
 
 /// Get the downsampling factor of a given level.
-pub unsafe fn get_level_downsample(osr: *const OpenSlideT, level: i32) -> Result<f64, Error> {
+pub unsafe fn get_level_downsample(
+    osr: *const OpenSlideT,
+    level: i32,
+) -> Result<f64, OpenSlideError> {
     let downsampling_factor = openslide_get_level_downsample(osr, level); // This is unsafe
     Ok(downsampling_factor)
 }
@@ -158,7 +184,7 @@ pub unsafe fn get_level_downsample(osr: *const OpenSlideT, level: i32) -> Result
 pub unsafe fn get_best_level_for_downsample(
     osr: *const OpenSlideT,
     downsample: f64,
-) -> Result<i32, Error> {
+) -> Result<i32, OpenSlideError> {
     let level = openslide_get_best_level_for_downsample(osr, downsample); // This is unsafe
     Ok(level)
 }
@@ -171,11 +197,10 @@ pub unsafe fn read_region(
     level: i32,
     w: i64,
     h: i64,
-) -> Result<Vec<u32>, Error> {
-    let mut buffer: Vec<libc::uint32_t> = Vec::with_capacity((h * w) as usize);
+) -> Result<Vec<u32>, OpenSlideError> {
+    let mut buffer: Vec<libc::uint32_t> = vec![0; (h * w) as usize];
     let p_buffer = buffer.as_mut_ptr();
     openslide_read_region(osr, p_buffer, x, y, level, w, h); // This is unsafe
-    buffer.set_len((h * w) as usize);
     Ok(buffer)
 }
 
@@ -183,25 +208,29 @@ pub unsafe fn read_region(
 // Error handling
 // ---------------
 
-/* FIXME Keep commented as long as it is not working. Gets segmentation fault core dumped
 /// Get the current error string.
-pub fn get_error(
-    osr: *const OpenSlideT
-) -> Result<String, Error> {
-    let msg = unsafe {
-        let c_msg = openslide_get_error(osr);
-        ffi::CStr::from_ptr(c_msg).to_string_lossy().into_owned()
-    };
-    Ok(msg)
+///
+/// Returns `None` if no error has occurred. Once an error has occurred, the OpenSlide object is
+/// unusable, and only `close` should be called on it.
+///
+/// # Safety
+///
+/// `osr` must be a live handle returned by `open` that has not been closed.
+pub unsafe fn get_error(osr: *const OpenSlideT) -> Option<String> {
+    let c_msg = openslide_get_error(osr); // This is unsafe
+    if c_msg.is_null() {
+        None
+    } else {
+        Some(ffi::CStr::from_ptr(c_msg).to_string_lossy().into_owned())
+    }
 }
-*/
 
 // ---------------
 // Properties
 // ---------------
 
 /// Get the NULL-terminated array of property names.
-pub unsafe fn get_property_names(osr: *const OpenSlideT) -> Result<Vec<String>, Error> {
+pub unsafe fn get_property_names(osr: *const OpenSlideT) -> Result<Vec<String>, OpenSlideError> {
     let string_values = {
         let null_terminated_array_ptr = openslide_get_property_names(osr);
         let mut counter = 0;
@@ -224,7 +253,10 @@ pub unsafe fn get_property_names(osr: *const OpenSlideT) -> Result<Vec<String>,
 }
 
 /// Get the value of a single property.
-pub unsafe fn get_property_value(osr: *const OpenSlideT, name: &str) -> Result<String, Error> {
+pub unsafe fn get_property_value(
+    osr: *const OpenSlideT,
+    name: &str,
+) -> Result<String, OpenSlideError> {
     let c_name = ffi::CString::new(name)?;
     let value = {
         let c_value = openslide_get_property_value(osr, c_name.as_ptr());
@@ -239,7 +271,9 @@ pub unsafe fn get_property_value(osr: *const OpenSlideT, name: &str) -> Result<S
 // ---------------
 
 /// Get the NULL-terminated array of property names.
-pub unsafe fn get_associated_image_names(osr: *const OpenSlideT) -> Result<Vec<String>, Error> {
+pub unsafe fn get_associated_image_names(
+    osr: *const OpenSlideT,
+) -> Result<Vec<String>, OpenSlideError> {
     let string_values = {
         let null_terminated_array_ptr = openslide_get_associated_image_names(osr);
         let mut counter = 0;
@@ -261,7 +295,10 @@ pub unsafe fn get_associated_image_names(osr: *const OpenSlideT) -> Result<Vec<S
     Ok(string_values)
 }
 
-pub unsafe fn get_associated_image_dimensions(osr: *const OpenSlideT, name: &str) -> Result<(i64, i64), Error> {
+pub unsafe fn get_associated_image_dimensions(
+    osr: *const OpenSlideT,
+    name: &str,
+) -> Result<(i64, i64), OpenSlideError> {
     let c_name = ffi::CString::new(name)?;
     let mut width: libc::int64_t = 0;
     let mut height: libc::int64_t = 0;
@@ -273,8 +310,13 @@ pub unsafe fn get_associated_image_dimensions(osr: *const OpenSlideT, name: &str
 pub unsafe fn read_associated_image(
     osr: *const OpenSlideT,
     name: &str
-) -> Result<Vec<u32>, Error> {
+) -> Result<Vec<u32>, OpenSlideError> {
     let (w, h) = get_associated_image_dimensions(osr, &name)?;
+    if w < 0 || h < 0 {
+        return Err(OpenSlideError::OpenSlideInternal(get_error(osr).unwrap_or_else(|| {
+            format!("Associated image {} has dimensions ({}, {})", name, w, h)
+        })));
+    }
     let c_name = ffi::CString::new(name)?;
     let mut buffer: Vec<libc::uint32_t> = Vec::with_capacity((h * w) as usize);
     let p_buffer = buffer.as_mut_ptr();
@@ -283,3 +325,20 @@ pub unsafe fn read_associated_image(
     Ok(buffer)
 }
 
+// ---------------
+// Utility functions
+// ---------------
+
+/// Get the version of the OpenSlide library.
+pub fn get_version() -> Result<String, OpenSlideError> {
+    let version = unsafe {
+        let c_version = openslide_get_version();
+        if c_version.is_null() {
+            return Err(OpenSlideError::OpenSlideInternal(String::from(
+                "OpenSlide did not return a version",
+            )));
+        }
+        ffi::CStr::from_ptr(c_version).to_string_lossy().into_owned()
+    };
+    Ok(version)
+}
@@ -3,14 +3,26 @@
 //!
 
 use std::cmp::PartialOrd;
-use std::collections::HashMap;
-use std::fmt::{Debug, Display};
-use std::path::Path;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Debug, Display};
+use std::hash::{Hash, Hasher};
+use std::{fs, iter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
-use failure::{format_err, Error};
-use image::RgbaImage;
+use error::OpenSlideError;
+use image::{
+    imageops, DynamicImage, FilterType, GrayImage, ImageBuffer, ImageFormat, Rgb, RgbImage, Rgba,
+    RgbaImage,
+};
 use num::zero;
 use num::{Integer, Num, ToPrimitive, Unsigned};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 use {bindings, properties, utils};
 
@@ -20,377 +32,3463 @@ use {bindings, properties, utils};
 /// API for rust. It also contains some other convenience methods.
 #[derive(Clone)]
 pub struct OpenSlide {
-    osr: *const bindings::OpenSlideT,
+    osr: Arc<OsrHandle>,
+    filename: PathBuf,
+    verify_region_dimensions: bool,
+    word_representation: utils::WordRepresentation,
+    tile_read_callback: Option<TileReadCallback>,
+    /// Orientation that `read_region` makes upright, if `OpenSlideBuilder::auto_orient()` is set
+    orientation: Option<utils::Orientation>,
+    /// Decoded associated images by name, each decoded on its first use. They are few and small,
+    /// and never change.
+    associated_images: RefCell<HashMap<String, RgbaImage>>,
     pub properties: properties::Properties,
 }
 
-impl Drop for OpenSlide {
-    /// This method is called when the object in dropped, and tries to close the slide.
+/// An open OpenSlide handle, closed when dropped
+///
+/// `OpenSlide` keeps it behind an `Arc`, so that clones share the handle and it is only closed
+/// once the last clone is dropped.
+struct OsrHandle(*const bindings::OpenSlideT);
+
+// The OpenSlide C library documents its handles as thread-safe: all functions except
+// `openslide_close` can be called concurrently on one handle from several threads. The handle is
+// only closed on drop, when no other reference to it is left.
+unsafe impl Send for OsrHandle {}
+unsafe impl Sync for OsrHandle {}
+
+impl Drop for OsrHandle {
     fn drop(&mut self) {
-        unsafe { bindings::close(self.osr) };
+        unsafe { bindings::close(self.0) };
+    }
+}
+
+/// A read of pixels from OpenSlide, as reported to the callback set with
+/// `OpenSlideBuilder::tile_read_callback()`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TileReadEvent {
+    /// Level the pixels were read from
+    pub level: u32,
+    /// (row, column) of the top left corner in level 0 coordinates, negative for reads starting
+    /// above or left of the slide
+    pub top_left_lvl0: (i64, i64),
+    /// (height, width) in pixels at `level`
+    pub size: (u64, u64),
+    /// Time spent in OpenSlide
+    pub duration: Duration,
+}
+
+/// A shared tile read callback, see `OpenSlideBuilder::tile_read_callback()`.
+#[derive(Clone)]
+struct TileReadCallback(Arc<dyn Fn(TileReadEvent) + Send + Sync>);
+
+impl Debug for TileReadCallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TileReadCallback")
+    }
+}
+
+/// Saturation above which a pixel is considered to be foreground (tissue).
+const FOREGROUND_SATURATION_THRESHOLD: f64 = 0.07;
+
+/// Approximate side length in pixels of the proxy read by `OpenSlide::is_region_blank()`.
+const BLANK_PROXY_SIZE: u64 = 8;
+
+/// Side length in pixels of the tiles streamed by `OpenSlide::global_histogram()`.
+const HISTOGRAM_TILE_SIZE: u64 = 1024;
+
+/// A plan for extracting approximately a given number of foreground tiles from a slide
+///
+/// See `OpenSlide::plan_tiling()`.
+#[derive(Clone, Debug)]
+pub struct TilingPlan {
+    /// Level to extract tiles from
+    pub level: u32,
+    /// Side length in pixels (at `level`) of the square tiles
+    pub tile_size: u32,
+    /// Distance in pixels (at `level`) between the top left corners of neighbouring tiles. This
+    /// is smaller than `tile_size` when the tiles need to overlap to reach the requested count,
+    /// and larger when they can be spread out.
+    pub stride: u32,
+    /// Estimated fraction of the slide that is foreground
+    pub foreground_fraction: f64,
+    /// Estimated number of foreground tiles
+    pub estimated_tile_count: usize,
+}
+
+/// An iterator over the tiles covering a whole level, in row major order
+///
+/// See `OpenSlide::tiles()`. Every item is the (row, column) of the tile in the grid of tiles,
+/// and the tile itself. A tile is only read when its item is requested.
+#[derive(Clone)]
+pub struct TileIterator<'a> {
+    slide: &'a OpenSlide,
+    level: u32,
+    downsample_factor: f64,
+    /// (height, width) of the level
+    level_size: (u64, u64),
+    /// (height, width) of the tiles
+    tile_size: (u64, u64),
+    /// (rows, columns) in the grid of tiles
+    grid_size: (u64, u64),
+    /// Row major position in the grid of the next tile
+    next_tile: u64,
+}
+
+impl<'a> Iterator for TileIterator<'a> {
+    type Item = Result<(u32, u32, RgbaImage), OpenSlideError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (num_tile_rows, num_tile_cols) = self.grid_size;
+        if self.next_tile >= num_tile_rows * num_tile_cols {
+            return None;
+        }
+        let tile_row = self.next_tile / num_tile_cols;
+        let tile_col = self.next_tile % num_tile_cols;
+        self.next_tile += 1;
+
+        let (height, width) = self.level_size;
+        let (tile_height, tile_width) = self.tile_size;
+        let row = tile_row * tile_height;
+        let col = tile_col * tile_width;
+        let tile = self.slide.read_region_stored(
+            utils::level_to_lvl0_coordinate(row, self.downsample_factor),
+            utils::level_to_lvl0_coordinate(col, self.downsample_factor),
+            u64::from(self.level),
+            tile_height.min(height - row),
+            tile_width.min(width - col),
+        );
+        Some(tile.map(|tile| (tile_row as u32, tile_col as u32, tile)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (num_tile_rows, num_tile_cols) = self.grid_size;
+        let remaining = (num_tile_rows * num_tile_cols - self.next_tile) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for TileIterator<'a> {}
+
+/// The reads of a slide that a `DeepZoomGenerator` needs
+///
+/// `OpenSlide` implements this with its methods of the same names. Other implementations can e.g.
+/// be alternative backends, or mock slides for testing the tiling of a `DeepZoomGenerator`.
+pub trait SlideReader {
+    /// Get the number of levels, see `OpenSlide::get_level_count`.
+    fn get_level_count(&self) -> Result<u32, OpenSlideError>;
+
+    /// Get the (width, height) of a level, see `OpenSlide::get_level_dimensions`.
+    fn get_level_dimensions(&self, level: u32) -> Result<(u64, u64), OpenSlideError>;
+
+    /// Get the downsample factor of a level, see `OpenSlide::get_level_downsample`.
+    fn get_level_downsample(&self, level: u32) -> Result<f64, OpenSlideError>;
+
+    /// Read a region as stored, see `OpenSlide::read_region`. The level dimensions are of the slide
+    /// as stored, so an `OpenSlide` reads regions as stored even with `auto_orient` here.
+    fn read_region(
+        &self,
+        top_left_lvl0_row: u64,
+        top_left_lvl0_col: u64,
+        level: u32,
+        height: u64,
+        width: u64,
+    ) -> Result<RgbaImage, OpenSlideError>;
+
+    /// Get the (x, y, width, height) of the non-empty region of the slide, see
+    /// `OpenSlide::get_bounds`. By default, the slide has no bounds.
+    fn get_bounds(&self) -> Result<Option<(u64, u64, u64, u64)>, OpenSlideError> {
+        Ok(None)
+    }
+
+    /// Get the background color of the slide, see `OpenSlide::background_color`. By default, it
+    /// is white.
+    fn background_color(&self) -> Result<Rgb<u8>, OpenSlideError> {
+        Ok(Rgb([255, 255, 255]))
+    }
+}
+
+impl SlideReader for OpenSlide {
+    fn get_level_count(&self) -> Result<u32, OpenSlideError> {
+        OpenSlide::get_level_count(self)
+    }
+
+    fn get_level_dimensions(&self, level: u32) -> Result<(u64, u64), OpenSlideError> {
+        OpenSlide::get_level_dimensions(self, level)
+    }
+
+    fn get_level_downsample(&self, level: u32) -> Result<f64, OpenSlideError> {
+        OpenSlide::get_level_downsample(self, level)
+    }
+
+    fn read_region(
+        &self,
+        top_left_lvl0_row: u64,
+        top_left_lvl0_col: u64,
+        level: u32,
+        height: u64,
+        width: u64,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        self.read_region_stored(
+            top_left_lvl0_row,
+            top_left_lvl0_col,
+            u64::from(level),
+            height,
+            width,
+        )
+    }
+
+    fn get_bounds(&self) -> Result<Option<(u64, u64, u64, u64)>, OpenSlideError> {
+        OpenSlide::get_bounds(self)
+    }
+
+    fn background_color(&self) -> Result<Rgb<u8>, OpenSlideError> {
+        OpenSlide::background_color(self)
+    }
+}
+
+/// A Deep Zoom tile source for a slide, e.g. for serving a slide to OpenSeadragon
+///
+/// This works like `openslide.deepzoom.DeepZoomGenerator` in the OpenSlide Python bindings. The
+/// Deep Zoom pyramid starts with the level 0 dimensions at the highest Deep Zoom level, and
+/// halves them (rounding up) for every level below it, down to 1 x 1 pixels at Deep Zoom level 0.
+/// Every Deep Zoom level is read from the best OpenSlide level for its downsample factor, and
+/// resized to the Deep Zoom level. Tiles are `tile_size` x `tile_size` pixels, plus `overlap`
+/// pixels on every side that has a neighbouring tile, and cut at the right and bottom edges.
+///
+/// Like in Deep Zoom, dimensions are (width, height) and tile addresses are (column, row). The
+/// slide is an `OpenSlide` by default, and can be any `SlideReader`.
+pub struct DeepZoomGenerator<'a, R: SlideReader + 'a = OpenSlide> {
+    slide: &'a R,
+    tile_size: u32,
+    overlap: u32,
+    /// Level 0 (column, row) of the top left corner of the tiled area
+    l0_offset: (u64, u64),
+    /// (width, height) of the tiled area at every OpenSlide level
+    level_dimensions: Vec<(u64, u64)>,
+    /// Downsample factor of every OpenSlide level
+    level_downsamples: Vec<f64>,
+    /// (width, height) of every Deep Zoom level
+    dz_dimensions: Vec<(u64, u64)>,
+    /// (columns, rows) of tiles at every Deep Zoom level
+    dz_tiles: Vec<(u64, u64)>,
+    /// OpenSlide level that every Deep Zoom level is read from
+    dz_slide_levels: Vec<u32>,
+    /// Downsample factor of every Deep Zoom level relative to the OpenSlide level it is read from
+    dz_downsamples: Vec<f64>,
+    background: Rgb<u8>,
+}
+
+// Not derived, as that would need `R: Clone`
+impl<'a, R: SlideReader> Clone for DeepZoomGenerator<'a, R> {
+    fn clone(&self) -> Self {
+        DeepZoomGenerator {
+            slide: self.slide,
+            tile_size: self.tile_size,
+            overlap: self.overlap,
+            l0_offset: self.l0_offset,
+            level_dimensions: self.level_dimensions.clone(),
+            level_downsamples: self.level_downsamples.clone(),
+            dz_dimensions: self.dz_dimensions.clone(),
+            dz_tiles: self.dz_tiles.clone(),
+            dz_slide_levels: self.dz_slide_levels.clone(),
+            dz_downsamples: self.dz_downsamples.clone(),
+            background: self.background,
+        }
+    }
+}
+
+impl<'a, R: SlideReader> DeepZoomGenerator<'a, R> {
+    /// Create a Deep Zoom tile source for a slide.
+    ///
+    /// The overlap can not be larger than the tile size. With `limit_bounds`, only the non-empty
+    /// region of the slide given by `get_bounds` is tiled, if the slide has bounds. Otherwise, the
+    /// whole slide is tiled. The tiles are composited over the background color of the slide
+    /// (`openslide.background-color`, white if the slide does not have one).
+    pub fn new(
+        slide: &'a R,
+        tile_size: u32,
+        overlap: u32,
+        limit_bounds: bool,
+    ) -> Result<Self, OpenSlideError> {
+        if tile_size == 0 || overlap > tile_size {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Tile size must be positive, and at least the overlap, got tile size {} \
+                 and overlap {}",
+                tile_size, overlap
+            )));
+        }
+        let num_levels = slide.get_level_count()?;
+        let mut level_dimensions = (0..num_levels)
+            .map(|level| slide.get_level_dimensions(level))
+            .collect::<Result<Vec<(u64, u64)>, OpenSlideError>>()?;
+        let level_downsamples = (0..num_levels)
+            .map(|level| slide.get_level_downsample(level))
+            .collect::<Result<Vec<f64>, OpenSlideError>>()?;
+        if level_dimensions.is_empty() {
+            return Err(OpenSlideError::OpenSlideInternal(String::from(
+                "Slide has no levels",
+            )));
+        }
+
+        let mut l0_offset = (0, 0);
+        if let (true, Some((x, y, width, height))) = (limit_bounds, slide.get_bounds()?) {
+            let (l0_width, l0_height) = level_dimensions[0];
+            let scale_x = width as f64 / l0_width as f64;
+            let scale_y = height as f64 / l0_height as f64;
+            for dimensions in &mut level_dimensions {
+                *dimensions = (
+                    (dimensions.0 as f64 * scale_x).ceil() as u64,
+                    (dimensions.1 as f64 * scale_y).ceil() as u64,
+                );
+            }
+            l0_offset = (x, y);
+        }
+
+        let mut dz_size = level_dimensions[0];
+        let mut dz_dimensions = vec![dz_size];
+        while dz_size.0 > 1 || dz_size.1 > 1 {
+            dz_size = (dz_size.0.div_ceil(2).max(1), dz_size.1.div_ceil(2).max(1));
+            dz_dimensions.push(dz_size);
+        }
+        dz_dimensions.reverse();
+
+        let tile = u64::from(tile_size);
+        let dz_tiles = dz_dimensions
+            .iter()
+            .map(|&(width, height)| (width.div_ceil(tile), height.div_ceil(tile)))
+            .collect();
+
+        let num_dz_levels = dz_dimensions.len();
+        let mut dz_slide_levels = Vec::with_capacity(num_dz_levels);
+        let mut dz_downsamples = Vec::with_capacity(num_dz_levels);
+        for dz_level in 0..num_dz_levels {
+            let l0_downsample = 2f64.powi((num_dz_levels - dz_level - 1) as i32);
+            let level = utils::best_level_for_downsample(&level_downsamples, l0_downsample);
+            dz_slide_levels.push(level as u32);
+            dz_downsamples.push(l0_downsample / level_downsamples[level]);
+        }
+
+        Ok(DeepZoomGenerator {
+            slide,
+            tile_size,
+            overlap,
+            l0_offset,
+            level_dimensions,
+            level_downsamples,
+            dz_dimensions,
+            dz_tiles,
+            dz_slide_levels,
+            dz_downsamples,
+            background: slide.background_color()?,
+        })
+    }
+
+    /// Get the number of Deep Zoom levels.
+    pub fn level_count(&self) -> u32 {
+        self.dz_dimensions.len() as u32
+    }
+
+    /// Get the (width, height) in pixels of a Deep Zoom level.
+    pub fn level_dimensions(&self, dz_level: u32) -> Result<(u64, u64), OpenSlideError> {
+        self.assert_dz_level_validity(dz_level)?;
+        Ok(self.dz_dimensions[dz_level as usize])
+    }
+
+    /// Get the number of (columns, rows) of tiles of a Deep Zoom level.
+    pub fn level_tiles(&self, dz_level: u32) -> Result<(u64, u64), OpenSlideError> {
+        self.assert_dz_level_validity(dz_level)?;
+        Ok(self.dz_tiles[dz_level as usize])
+    }
+
+    /// Get the tile at the (column, row) `address` of a Deep Zoom level.
+    ///
+    /// The tile is opaque, with the transparent parts of the slide composited over the
+    /// background color.
+    pub fn get_tile(
+        &self,
+        dz_level: u32,
+        address: (u64, u64),
+    ) -> Result<RgbaImage, OpenSlideError> {
+        self.assert_dz_level_validity(dz_level)?;
+        let dz_level = dz_level as usize;
+        let (num_cols, num_rows) = self.dz_tiles[dz_level];
+        if address.0 >= num_cols || address.1 >= num_rows {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Tile address (column, row) {:?} is outside the ({}, {}) tiles of Deep \
+                 Zoom level {}",
+                address, num_cols, num_rows, dz_level
+            )));
+        }
+
+        let slide_level = self.dz_slide_levels[dz_level];
+        let (col_axis, out_width) = self.tile_axis(dz_level, address.0, num_cols, |d| d.0);
+        let (row_axis, out_height) = self.tile_axis(dz_level, address.1, num_rows, |d| d.1);
+        let tile = self.slide.read_region(
+            row_axis.0 + self.l0_offset.1,
+            col_axis.0 + self.l0_offset.0,
+            slide_level,
+            row_axis.1,
+            col_axis.1,
+        )?;
+        let tile = DynamicImage::ImageRgb8(utils::composite_over(&tile, self.background)).to_rgba();
+
+        let (out_width, out_height) = (out_width as u32, out_height as u32);
+        if tile.dimensions() == (out_width, out_height) {
+            return Ok(tile);
+        }
+        Ok(imageops::resize(&tile, out_width, out_height, FilterType::Lanczos3))
+    }
+
+    /// Compute the read of a tile along one axis.
+    ///
+    /// Returns the level 0 position (without the offset of the tiled area) and the size at the
+    /// OpenSlide level of the read, and the size of the tile at the Deep Zoom level. `axis`
+    /// selects the axis from (width, height) dimensions.
+    fn tile_axis<F: Fn((u64, u64)) -> u64>(
+        &self,
+        dz_level: usize,
+        tile_index: u64,
+        num_tiles: u64,
+        axis: F,
+    ) -> ((u64, u64), u64) {
+        let tile_size = u64::from(self.tile_size);
+        let overlap = u64::from(self.overlap);
+        let overlap_before = if tile_index != 0 { overlap } else { 0 };
+        let overlap_after = if tile_index != num_tiles - 1 { overlap } else { 0 };
+        let dz_limit = axis(self.dz_dimensions[dz_level]);
+        let dz_size =
+            tile_size.min(dz_limit - tile_size * tile_index) + overlap_before + overlap_after;
+
+        let slide_level = self.dz_slide_levels[dz_level] as usize;
+        let dz_downsample = self.dz_downsamples[dz_level];
+        let level_position = dz_downsample * (tile_size * tile_index - overlap_before) as f64;
+        let l0_position = (self.level_downsamples[slide_level] * level_position) as u64;
+        let level_limit = axis(self.level_dimensions[slide_level]) as f64;
+        let level_size = (dz_downsample * dz_size as f64)
+            .ceil()
+            .min(level_limit - level_position.ceil()) as u64;
+        ((l0_position, level_size), dz_size)
+    }
+
+    fn assert_dz_level_validity(&self, dz_level: u32) -> Result<(), OpenSlideError> {
+        if dz_level >= self.level_count() {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Specified Deep Zoom level {} is larger than the max Deep Zoom level {}",
+                dz_level, self.level_count() - 1,
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// An OpenSlide object that can be shared between threads, e.g. in an `Arc`
+///
+/// `OpenSlide` can be sent to another thread, but it is not `Sync`, as it decodes its associated
+/// images lazily into a cache that is not thread-safe. `SyncOpenSlide` serializes every call
+/// through a `Mutex`, so it is `Sync` as well.
+///
+/// No two operations run concurrently: a call waits for the call of any other thread to finish,
+/// e.g. a large `read_region` blocks all other reads. Only the `properties` field, parsed when the
+/// slide is opened, can be used without locking. For parallel reads of one slide, use
+/// `OpenSlide::read_regions_par()`, or give every thread a clone of an `OpenSlide`; the clones
+/// share the handle, which OpenSlide allows to be used from several threads at once.
+pub struct SyncOpenSlide {
+    slide: Mutex<OpenSlide>,
+    pub properties: properties::Properties,
+}
+
+impl SyncOpenSlide {
+    /// Open the slide at the given filename location, like `OpenSlide::new`.
+    pub fn new<P: AsRef<Path>>(filename: P) -> Result<SyncOpenSlide, OpenSlideError> {
+        let slide = OpenSlide::new(filename)?;
+        Ok(SyncOpenSlide {
+            properties: slide.properties.clone(),
+            slide: Mutex::new(slide),
+        })
+    }
+
+    /// Get the number of levels in the whole slide image, see `OpenSlide::get_level_count`.
+    pub fn get_level_count(&self) -> Result<u32, OpenSlideError> {
+        self.lock().get_level_count()
+    }
+
+    /// Get the dimensions of level 0, see `OpenSlide::get_level0_dimensions`.
+    pub fn get_level0_dimensions(&self) -> Result<(u64, u64), OpenSlideError> {
+        self.lock().get_level0_dimensions()
+    }
+
+    /// Get the dimensions of a level, see `OpenSlide::get_level_dimensions`.
+    pub fn get_level_dimensions(&self, level: u32) -> Result<(u64, u64), OpenSlideError> {
+        self.lock().get_level_dimensions(level)
+    }
+
+    /// Get the downsampling factor of a level, see `OpenSlide::get_level_downsample`.
+    pub fn get_level_downsample(&self, level: u32) -> Result<f64, OpenSlideError> {
+        self.lock().get_level_downsample(level)
+    }
+
+    /// Get the best level for a downsample factor, see
+    /// `OpenSlide::get_best_level_for_downsample`.
+    pub fn get_best_level_for_downsample(
+        &self,
+        downsample_factor: f64,
+    ) -> Result<u32, OpenSlideError> {
+        self.lock().get_best_level_for_downsample(downsample_factor)
+    }
+
+    /// Get a dictionary of properties, see `OpenSlide::get_properties`.
+    pub fn get_properties(&self) -> Result<HashMap<String, String>, OpenSlideError> {
+        self.lock().get_properties()
+    }
+
+    /// Get the names of the associated images, see `OpenSlide::get_associated_image_names`.
+    pub fn get_associated_image_names(&self) -> Result<Vec<String>, OpenSlideError> {
+        self.lock().get_associated_image_names()
+    }
+
+    /// Read the associated image with the given name, see `OpenSlide::read_associated_image`.
+    pub fn read_associated_image(&self, name: &str) -> Result<RgbaImage, OpenSlideError> {
+        self.lock().read_associated_image(name)
+    }
+
+    /// Read a region, see `OpenSlide::read_region`.
+    pub fn read_region<T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy>(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        self.lock().read_region(top_left_lvl0_row, top_left_lvl0_col, level, height, width)
+    }
+
+    /// Get the error message from OpenSlide, see `OpenSlide::get_error`.
+    pub fn get_error(&self) -> Option<String> {
+        self.lock().get_error()
+    }
+
+    /// Lock the slide. A panic of another thread while it held the lock does not leave the slide
+    /// half updated, so a poisoned lock is recovered.
+    fn lock<'a>(&'a self) -> MutexGuard<'a, OpenSlide> {
+        self.slide.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// The capacity of the cache of a `CachedOpenSlide`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CacheCapacity {
+    /// At most this many regions
+    Regions(usize),
+    /// At most this many bytes of decoded pixels (4 bytes per pixel)
+    Bytes(usize),
+}
+
+/// Statistics of the cache of a `CachedOpenSlide`, see `CachedOpenSlide::stats()`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Reads copied from the cache
+    pub hits: u64,
+    /// Reads of regions that were not cached
+    pub misses: u64,
+    /// Regions evicted to fit the capacity
+    pub evictions: u64,
+    /// Bytes of the decoded pixels of the cached regions
+    pub bytes: usize,
+}
+
+/// An OpenSlide object that keeps the most recently read regions in memory
+///
+/// Like handles (see `OpenSlide::new`), decoded regions are expensive to make, and a tile server
+/// is often asked for the same tiles again. `CachedOpenSlide::read_region` returns a copy of the
+/// cached image if the same region (the same level, position and size) was read before, and reads
+/// and caches it otherwise. When the cache exceeds its capacity, the least recently used regions
+/// are evicted. A region larger than the whole capacity is returned without being cached.
+pub struct CachedOpenSlide {
+    slide: OpenSlide,
+    capacity: CacheCapacity,
+    cache: RefCell<RegionCache>,
+}
+
+/// The cached regions, with the time of their last use
+#[derive(Default)]
+struct RegionCache {
+    images: HashMap<Region, (RgbaImage, u64)>,
+    /// Cached regions by the time of their last use, oldest first
+    last_used: BTreeMap<u64, Region>,
+    time: u64,
+    num_bytes: usize,
+    /// Hits, misses and evictions since the cache was made or its statistics were reset
+    stats: CacheStats,
+}
+
+impl CachedOpenSlide {
+    /// Cache the regions read from `slide`, up to `capacity`.
+    pub fn new(slide: OpenSlide, capacity: CacheCapacity) -> CachedOpenSlide {
+        CachedOpenSlide {
+            slide,
+            capacity,
+            cache: RefCell::new(RegionCache::default()),
+        }
+    }
+
+    /// The slide, e.g. for reads that are not cached.
+    pub fn slide(&self) -> &OpenSlide {
+        &self.slide
+    }
+
+    /// Read a region, or copy it from the cache. The arguments are the same as for
+    /// `OpenSlide::read_region`.
+    pub fn read_region(
+        &self,
+        top_left_lvl0_row: u64,
+        top_left_lvl0_col: u64,
+        level: u32,
+        height: u64,
+        width: u64,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        let region = Region {
+            top_left_lvl0_row,
+            top_left_lvl0_col,
+            level,
+            height,
+            width,
+        };
+        if let Some(image) = self.cache.borrow_mut().get(&region) {
+            return Ok(image);
+        }
+
+        let image = self.slide.read_region(
+            top_left_lvl0_row,
+            top_left_lvl0_col,
+            u64::from(level),
+            height,
+            width,
+        )?;
+        self.cache.borrow_mut().insert(region, image.clone(), self.capacity);
+        Ok(image)
+    }
+
+    /// Check whether a region is cached, without counting it as used.
+    pub fn contains(&self, region: &Region) -> bool {
+        self.cache.borrow().images.contains_key(region)
+    }
+
+    /// The number of cached regions.
+    pub fn len(&self) -> usize {
+        self.cache.borrow().images.len()
+    }
+
+    /// Check whether no region is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of bytes of the decoded pixels of the cached regions.
+    pub fn num_bytes(&self) -> usize {
+        self.cache.borrow().num_bytes
+    }
+
+    /// Remove every region from the cache. The statistics (see `stats`) are kept.
+    pub fn clear(&self) {
+        let mut cache = self.cache.borrow_mut();
+        *cache = RegionCache {
+            stats: cache.stats,
+            ..RegionCache::default()
+        };
+    }
+
+    /// The hits, misses and evictions of the cache since it was made or `reset_stats` was called,
+    /// and the bytes it holds now.
+    ///
+    /// E.g. the hit ratio `hits / (hits + misses)` helps to choose the capacity.
+    pub fn stats(&self) -> CacheStats {
+        let cache = self.cache.borrow();
+        CacheStats {
+            bytes: cache.num_bytes,
+            ..cache.stats
+        }
+    }
+
+    /// Set the hits, misses and evictions of the cache to 0.
+    pub fn reset_stats(&self) {
+        self.cache.borrow_mut().stats = CacheStats::default();
+    }
+}
+
+impl RegionCache {
+    /// Copy a cached region, and mark it as the most recently used.
+    fn get(&mut self, region: &Region) -> Option<RgbaImage> {
+        self.time += 1;
+        let time = self.time;
+        let (image, last_used) = match self.images.get_mut(region) {
+            Some(cached) => cached,
+            None => {
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+        self.stats.hits += 1;
+        self.last_used.remove(last_used);
+        self.last_used.insert(time, *region);
+        *last_used = time;
+        Some(image.clone())
+    }
+
+    /// Cache a region, and evict the least recently used regions until the cache fits.
+    fn insert(&mut self, region: Region, image: RgbaImage, capacity: CacheCapacity) {
+        let num_bytes = image.len();
+        let fits = match capacity {
+            CacheCapacity::Regions(max_regions) => max_regions > 0,
+            CacheCapacity::Bytes(max_bytes) => num_bytes <= max_bytes,
+        };
+        if !fits {
+            return;
+        }
+
+        self.time += 1;
+        self.last_used.insert(self.time, region);
+        self.num_bytes += num_bytes;
+        if let Some((replaced, last_used)) = self.images.insert(region, (image, self.time)) {
+            self.last_used.remove(&last_used);
+            self.num_bytes -= replaced.len();
+        }
+        while self.exceeds(capacity) {
+            let oldest = *self.last_used.keys().next().expect("The cache is not empty");
+            let evicted = self.last_used.remove(&oldest).expect("The oldest region is cached");
+            if let Some((image, _)) = self.images.remove(&evicted) {
+                self.num_bytes -= image.len();
+                self.stats.evictions += 1;
+            }
+        }
+    }
+
+    fn exceeds(&self, capacity: CacheCapacity) -> bool {
+        match capacity {
+            CacheCapacity::Regions(max_regions) => self.images.len() > max_regions,
+            CacheCapacity::Bytes(max_bytes) => self.num_bytes > max_bytes,
+        }
+    }
+}
+
+/// Compression of the tiles of a level
+///
+/// See `OpenSlide::level_compression_info()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompressionInfo {
+    /// Compression type, e.g. "JPEG" or "J2K"
+    pub compression: String,
+    /// Compression quality (for JPEG, between 0 and 100), if recorded
+    pub quality: Option<u32>,
+}
+
+/// A region of a slide, with the same meaning as the arguments of `OpenSlide::read_region()`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Region {
+    /// Row coordinate (increasing downwards) of the top left pixel position at level 0
+    pub top_left_lvl0_row: u64,
+    /// Column coordinate (increasing to the right) of the top left pixel position at level 0
+    pub top_left_lvl0_col: u64,
+    /// Level to read the region from
+    pub level: u32,
+    /// Height in pixels (at `level`) of the region
+    pub height: u64,
+    /// Width in pixels (at `level`) of the region
+    pub width: u64,
+}
+
+/// Identifying properties of a slide, for use as e.g. a cache key
+///
+/// See `OpenSlide::fingerprint()`. Two fingerprints are equal if all their fields are equal (the
+/// pixel spacings compare bitwise).
+#[derive(Clone, Debug)]
+pub struct SlideFingerprint {
+    /// The `openslide.quickhash-1` property, a hash of the slide contents computed by OpenSlide.
+    /// Not every slide has it.
+    pub quickhash_1: Option<String>,
+    /// (width, height) of level 0
+    pub level0_dimensions: (u64, u64),
+    /// The `openslide.vendor` property
+    pub vendor: Option<String>,
+    /// Pixel spacing (x, y) in microns per pixel, see `OpenSlide::pixel_spacing()`
+    pub mpp: Option<(f64, f64)>,
+}
+
+impl SlideFingerprint {
+    fn mpp_bits(&self) -> Option<(u64, u64)> {
+        self.mpp.map(|(mpp_x, mpp_y)| (mpp_x.to_bits(), mpp_y.to_bits()))
+    }
+}
+
+impl PartialEq for SlideFingerprint {
+    fn eq(&self, other: &SlideFingerprint) -> bool {
+        self.quickhash_1 == other.quickhash_1
+            && self.level0_dimensions == other.level0_dimensions
+            && self.vendor == other.vendor
+            && self.mpp_bits() == other.mpp_bits()
+    }
+}
+
+impl Eq for SlideFingerprint {}
+
+impl Hash for SlideFingerprint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.quickhash_1.hash(state);
+        self.level0_dimensions.hash(state);
+        self.vendor.hash(state);
+        self.mpp_bits().hash(state);
+    }
+}
+
+impl Display for SlideFingerprint {
+    /// Formats as e.g. `5b8f1a... 2220x2967 aperio 0.499x0.499mpp`, with `-` for missing fields.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (width, height) = self.level0_dimensions;
+        write!(
+            f,
+            "{} {}x{} {} ",
+            self.quickhash_1.as_deref().unwrap_or("-"),
+            width,
+            height,
+            self.vendor.as_deref().unwrap_or("-"),
+        )?;
+        match self.mpp {
+            Some((mpp_x, mpp_y)) => write!(f, "{}x{}mpp", mpp_x, mpp_y),
+            None => write!(f, "-"),
+        }
+    }
+}
+
+/// A region read from a slide, with the level 0 rectangle its pixels cover
+///
+/// Returned by the reads that can adjust the requested region, such as
+/// `OpenSlide::read_region_clipped()` and `OpenSlide::read_max_detail_around()`.
+#[derive(Clone, Debug)]
+pub struct ReadRegion {
+    /// The pixels of the region
+    pub image: RgbaImage,
+    /// (top row, left column, height, width) of the region in level 0 coordinates
+    pub level0_rect: (u64, u64, u64, u64),
+    /// Level the region was read from
+    pub level: u32,
+}
+
+/// A labeled region of interest embedded in a slide
+///
+/// See `OpenSlide::embedded_annotations()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Annotation {
+    /// Label of the annotation, e.g. `region[0]`
+    pub label: String,
+    pub shape: AnnotationShape,
+}
+
+/// The shape of an `Annotation`, in level 0 coordinates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnnotationShape {
+    /// (top row, left column, height, width) of a rectangle
+    Rectangle((u64, u64, u64, u64)),
+    /// (row, column) of a point
+    Point((u64, u64)),
+}
+
+/// Relative tolerance around the nominal magnifications of `ScanTier`.
+const SCAN_TIER_TOLERANCE: f64 = 0.1;
+
+/// The nominal scan magnification of a slide
+///
+/// See `OpenSlide::scan_resolution_tier()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScanTier {
+    /// 10x
+    X10,
+    /// 20x
+    X20,
+    /// 40x
+    X40,
+    /// Any other magnification
+    Other(f64),
+}
+
+impl ScanTier {
+    /// Classify a magnification, allowing 10% deviation from the nominal magnifications.
+    pub fn from_magnification(magnification: f64) -> ScanTier {
+        let is_near =
+            |nominal: f64| (magnification - nominal).abs() <= nominal * SCAN_TIER_TOLERANCE;
+        if is_near(10.0) {
+            ScanTier::X10
+        } else if is_near(20.0) {
+            ScanTier::X20
+        } else if is_near(40.0) {
+            ScanTier::X40
+        } else {
+            ScanTier::Other(magnification)
+        }
+    }
+}
+
+/// A builder for `OpenSlide` objects with non-default options
+///
+/// `OpenSlide::new(filename)` is the same as `OpenSlideBuilder::new(filename).open()`.
+#[derive(Clone, Debug)]
+pub struct OpenSlideBuilder {
+    filename: PathBuf,
+    verify_region_dimensions: bool,
+    word_representation: utils::WordRepresentation,
+    tile_read_callback: Option<TileReadCallback>,
+    auto_orient: bool,
+}
+
+impl OpenSlideBuilder {
+    /// Start building an `OpenSlide` object for the slide at the given filename location.
+    pub fn new<P: AsRef<Path>>(filename: P) -> OpenSlideBuilder {
+        OpenSlideBuilder {
+            filename: filename.as_ref().to_path_buf(),
+            verify_region_dimensions: false,
+            word_representation: utils::WordRepresentation::default(),
+            tile_read_callback: None,
+            auto_orient: false,
+        }
+    }
+
+    /// Check that every region read with `OpenSlide::read_region` has the requested dimensions.
+    ///
+    /// In debug builds, a mismatch is always caught by a debug assertion. Enabling this option
+    /// also catches it in release builds, where `read_region` then returns an error instead of a
+    /// (possibly transposed) image.
+    pub fn verify_region_dimensions(mut self, verify: bool) -> OpenSlideBuilder {
+        self.verify_region_dimensions = verify;
+        self
+    }
+
+    /// Set the word representation used to decode the pixels of every read.
+    ///
+    /// The default, `WordRepresentation::Native`, is correct for the OpenSlide C library. Only
+    /// change this if the channels of decoded images come out swapped with your combination of
+    /// platform and OpenSlide build. See `utils::WordRepresentation`.
+    pub fn word_representation(
+        mut self,
+        word_representation: utils::WordRepresentation,
+    ) -> OpenSlideBuilder {
+        self.word_representation = word_representation;
+        self
+    }
+
+    /// Call `callback` after every read of pixels from OpenSlide, e.g. to trace I/O.
+    ///
+    /// Every region read (`read_region` and the methods built on it) reads its pixels in one or
+    /// more reads from OpenSlide, e.g. one per tile for `read_region_cancelable`. The callback
+    /// gets the level, position, size and duration of each of them. Without a callback, reads are
+    /// not timed. The callback is shared with the clones of the `OpenSlide`, which can call it
+    /// from other threads.
+    pub fn tile_read_callback(
+        mut self,
+        callback: Box<dyn Fn(TileReadEvent) + Send + Sync>,
+    ) -> OpenSlideBuilder {
+        self.tile_read_callback = Some(TileReadCallback(Arc::from(callback)));
+        self
+    }
+
+    /// Make every region read with `OpenSlide::read_region` upright.
+    ///
+    /// The regions are transformed according to `OpenSlide::native_orientation()`, which is
+    /// looked up once when the slide is opened. The arguments of `read_region` are still in the
+    /// coordinates of the stored slide, so with a transposing orientation, a region read with
+    /// (height, width) is returned with width rows and height columns. Only `read_region` itself
+    /// is affected; the other reads return the slide as stored.
+    pub fn auto_orient(mut self, auto_orient: bool) -> OpenSlideBuilder {
+        self.auto_orient = auto_orient;
+        self
+    }
+
+    /// Open the slide with the chosen options.
+    pub fn open(self) -> Result<OpenSlide, OpenSlideError> {
+        let mut slide = OpenSlide::new(&self.filename)?;
+        slide.verify_region_dimensions = self.verify_region_dimensions;
+        slide.word_representation = self.word_representation;
+        slide.tile_read_callback = self.tile_read_callback;
+        if self.auto_orient {
+            slide.orientation = Some(slide.native_orientation()?);
+        }
+        Ok(slide)
+    }
+}
+
+impl OpenSlide {
+    /// This method tries to open the slide at the given filename location.
+    ///
+    /// The filename can be anything that converts to a `Path`, e.g. a `&str` or a `PathBuf`.
+    ///
+    /// This function can be expensive; avoid calling it unnecessarily. For example, a tile server
+    /// should not create a new object on every tile request. Instead, it should maintain a cache
+    /// of OpenSlide objects and reuse them when possible.
+    pub fn new<P: AsRef<Path>>(filename: P) -> Result<OpenSlide, OpenSlideError> {
+        let filename = filename.as_ref();
+        if !filename.exists() {
+            return Err(OpenSlideError::NonexistentPath(filename.to_path_buf()));
+        }
+
+        // Wrapped right away, so that the handle is closed on every error below
+        let osr = OsrHandle(bindings::open(
+            filename
+                .to_str()
+                .ok_or_else(|| OpenSlideError::InvalidPath(filename.to_path_buf()))?,
+        )?);
+
+        let mut property_map = HashMap::<String, String>::new();
+        for name in unsafe { bindings::get_property_names(osr.0)? } {
+            let value = unsafe { bindings::get_property_value(osr.0, &name)? };
+            property_map.insert(name, value);
+        }
+        let properties = properties::Properties::new(&property_map)?;
+
+        Ok(OpenSlide {
+            osr: Arc::new(osr),
+            filename: filename.to_path_buf(),
+            verify_region_dimensions: false,
+            word_representation: utils::WordRepresentation::default(),
+            tile_read_callback: None,
+            orientation: None,
+            associated_images: RefCell::new(HashMap::new()),
+            properties,
+        })
+    }
+
+    /// Get the vendor (e.g. aperio or hamamatsu) of the slide at the given filename location.
+    ///
+    /// This is cheap compared to opening the slide, and returns an error if the slide is not
+    /// recognized by OpenSlide.
+    pub fn detect_vendor<P: AsRef<Path>>(filename: P) -> Result<String, OpenSlideError> {
+        let filename = filename.as_ref();
+        if !filename.exists() {
+            return Err(OpenSlideError::NonexistentPath(filename.to_path_buf()));
+        }
+        bindings::detect_vendor(
+            filename
+                .to_str()
+                .ok_or_else(|| OpenSlideError::InvalidPath(filename.to_path_buf()))?,
+        )
+    }
+
+    /// Get the version of the linked OpenSlide C library, e.g. "3.4.1".
+    pub fn library_version() -> Result<String, OpenSlideError> {
+        bindings::get_version()
+    }
+
+    /// Get the path the slide was opened from.
+    pub fn path(&self) -> &Path {
+        &self.filename
+    }
+
+    /// Open a new, independent handle to the same slide, with the same options.
+    ///
+    /// The new handle can e.g. be moved to another thread. Like `OpenSlide::new`, this can be
+    /// expensive.
+    pub fn reopen(&self) -> Result<OpenSlide, OpenSlideError> {
+        self.builder().open()
+    }
+
+    /// A builder for a new handle to the same slide, with the same options.
+    fn builder(&self) -> OpenSlideBuilder {
+        OpenSlideBuilder {
+            filename: self.filename.clone(),
+            verify_region_dimensions: self.verify_region_dimensions,
+            word_representation: self.word_representation,
+            tile_read_callback: self.tile_read_callback.clone(),
+            auto_orient: self.orientation.is_some(),
+        }
+    }
+
+    /// Get the error message from OpenSlide, or `None` if no error has occurred.
+    ///
+    /// OpenSlide keeps the first error of a failed operation, and once an error has occurred the
+    /// slide is unusable: every further call fails, e.g. `get_level_count()` returns the message
+    /// of the error. Open the slide again to continue reading it.
+    pub fn get_error(&self) -> Option<String> {
+        unsafe { bindings::get_error(self.osr.0) }
+    }
+
+    /// Get the number of levels in the whole slide image.
+    pub fn get_level_count(&self) -> Result<u32, OpenSlideError> {
+        let num_levels = unsafe { bindings::get_level_count(self.osr.0)? };
+
+        if num_levels < -1 {
+            Err(self.openslide_error(format!(
+                "Number of levels is {}, this is an unknown error from OpenSlide. \
+                 OpenSlide returns -1 if an error occured. \
+                 See OpenSlide C API documentation.",
+                num_levels
+            )))
+        } else if num_levels == -1 {
+            Err(self.openslide_error(String::from(
+                "Number of levels is -1, this is a known error from OpenSlide. \
+                 OpenSlide returns -1 if an error occured. \
+                 See OpenSlide C API documentation."
+            )))
+        } else {
+            Ok(num_levels as u32)
+        }
+    }
+
+    /// Get the dimensions of level 0 (the largest level).
+    ///
+    /// This method returns the (width, height) number of pixels of the level 0 whole slide image.
+    ///
+    /// This is the same as calling get_level_dimensions(level) with level=0: the OpenSlide C
+    /// library implements `openslide_get_level0_dimensions` as the dimensions of level 0, so the
+    /// two agree for every format.
+    pub fn get_level0_dimensions(&self) -> Result<(u64, u64), OpenSlideError> {
+        let (width, height) = unsafe { bindings::get_level0_dimensions(self.osr.0)? };
+
+        if width < -1 {
+            return Err(self.openslide_error(format!(
+                "Width is {}, this is an unknown error from OpenSlide. \
+                 OpenSlide returns -1 if an error occured. \
+                 See OpenSlide C API documentation.",
+                width
+            )));
+        } else if width == -1 {
+            return Err(self.openslide_error(String::from(
+                "Width is -1, this is a known error from OpenSlide. \
+                 OpenSlide returns -1 if an error occured. \
+                 See OpenSlide C API documentation."
+            )));
+        }
+
+        if height < -1 {
+            return Err(self.openslide_error(format!(
+                "Height is {}, this is an unknown error from OpenSlide. \
+                 OpenSlide returns -1 if an error occured. \
+                 See OpenSlide C API documentation.",
+                width
+            )));
+        } else if height == -1 {
+            return Err(self.openslide_error(String::from(
+                "Height is -1, this is a known error from OpenSlide. \
+                 OpenSlide returns -1 if an error occured. \
+                 See OpenSlide C API documentation."
+            )));
+        }
+
+        Ok((width as u64, height as u64))
+    }
+
+    /// Get the dimensions of level 0 (the largest level).
+    ///
+    /// This method returns the (width, height) number of pixels of the whole slide image at the
+    /// specified level. Returns an error if the level is invalid
+    pub fn get_level_dimensions<T: Integer + ToPrimitive + Debug + Display + Clone + Copy>(
+        &self,
+        level: T,
+    ) -> Result<(u64, u64), OpenSlideError> {
+        self.assert_level_validity(level)?;
+        let level = to_i32(level, "level")?;
+
+        let (width, height) = unsafe { bindings::get_level_dimensions(self.osr.0, level)?};
+
+        if width < -1 {
+            return Err(self.openslide_error(format!(
+                "Width is {}, this is an unknown error from OpenSlide. \
+                 OpenSlide returns -1 if an error occured. \
+                 See OpenSlide C API documentation.",
+                width
+            )));
+        } else if width == -1 {
+            return Err(self.openslide_error(String::from(
+                "Width is -1, this is a known error from openslide. \
+                 OpenSlide returns -1 if an error occured. \
+                 See OpenSlide C API documentation."
+            )));
+        }
+
+        if height < -1 {
+            return Err(self.openslide_error(format!(
+                "Height is {}, this is an unknown error from OpenSlide. \
+                 OpenSlide returns -1 if an error occured. \
+                 See OpenSlide C API documentation.",
+                width
+            )));
+        } else if height == -1 {
+            return Err(self.openslide_error(String::from(
+                "Height is -1, this is a known error from openslide. \
+                 OpenSlide returns -1 if an error occured. \
+                 See OpenSlide C API documentation."
+            )));
+        }
+
+        Ok((width as u64, height as u64))
+    }
+
+    /// Get the downsampling factor of a given level.
+    pub fn get_level_downsample<
+        T: Integer + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        level: T,
+    ) -> Result<f64, OpenSlideError> {
+        self.assert_level_validity(level)?;
+        let level = to_i32(level, "level")?;
+        let downsample_factor = unsafe { bindings::get_level_downsample(self.osr.0, level)? };
+
+        if downsample_factor < 0.0 {
+            return Err(self.openslide_error(format!(
+                "When trying to get a downsample factor for level {},\
+                 OpenSlide returned a downsample factor {}, this is an error from \
+                 OpenSlide. OpenSlide returns -1.0 if an error occured. \
+                 See OpenSlide C API documentation.",
+                level, downsample_factor
+            )));
+        }
+
+        Ok(downsample_factor)
+    }
+
+    /// Map a level 0 coordinate pair, e.g. (x, y), to the pixel grid of `level`.
+    ///
+    /// Both coordinates are divided by the downsample factor of the level, and rounded to the
+    /// nearest integer, see `utils::lvl0_to_level_coordinate`. Note that downsample factors need
+    /// not be exact powers of two (e.g. 4.000345), so a round trip through `level_to_level0` can
+    /// be off by a few level 0 pixels at the coarser levels.
+    pub fn level0_to_level(
+        &self,
+        level: u32,
+        lvl0: (u64, u64),
+    ) -> Result<(u64, u64), OpenSlideError> {
+        let downsample_factor = self.get_level_downsample(level)?;
+        Ok((
+            utils::lvl0_to_level_coordinate(lvl0.0, downsample_factor),
+            utils::lvl0_to_level_coordinate(lvl0.1, downsample_factor),
+        ))
+    }
+
+    /// Map a coordinate pair, e.g. (x, y), in the pixel grid of `level` to level 0.
+    ///
+    /// Both coordinates are multiplied by the downsample factor of the level, and rounded to the
+    /// nearest integer, see `utils::level_to_lvl0_coordinate`. This is the position to give to
+    /// `read_region` for a position at `level`.
+    pub fn level_to_level0(
+        &self,
+        level: u32,
+        coords: (u64, u64),
+    ) -> Result<(u64, u64), OpenSlideError> {
+        let downsample_factor = self.get_level_downsample(level)?;
+        Ok((
+            utils::level_to_lvl0_coordinate(coords.0, downsample_factor),
+            utils::level_to_lvl0_coordinate(coords.1, downsample_factor),
+        ))
+    }
+
+    /// Get the best level to use for displaying the given downsample factor.
+    ///
+    /// Level downsamples need not be powers of two, so they are compared with a small relative
+    /// tolerance, see `utils::best_level_for_downsample`.
+    pub fn get_best_level_for_downsample<
+        T: Num + ToPrimitive + PartialOrd + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        downsample_factor: T,
+    ) -> Result<u32, OpenSlideError> {
+        if downsample_factor < zero() {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Only non-negative downsample factor is allowed. \
+                 You specified {}. ",
+                downsample_factor
+            )));
+        }
+
+        let downsample_factor = to_f64(downsample_factor, "downsample_factor")?;
+        let level_downsamples = (0..self.get_level_count()?)
+            .map(|level| self.get_level_downsample(level))
+            .collect::<Result<Vec<f64>, OpenSlideError>>()?;
+
+        Ok(utils::best_level_for_downsample(&level_downsamples, downsample_factor) as u32)
+    }
+
+    /// Get the best level for displaying the slide at a given screen scale.
+    ///
+    /// `slide_pixels_per_screen_pixel` is the number of level 0 pixels per device pixel, e.g. the
+    /// zoom downsample divided by the device pixel ratio on a HiDPI display. This is then the
+    /// downsample factor given to `get_best_level_for_downsample`.
+    pub fn level_for_screen(
+        &self,
+        slide_pixels_per_screen_pixel: f64,
+    ) -> Result<u32, OpenSlideError> {
+        if !(slide_pixels_per_screen_pixel.is_finite() && slide_pixels_per_screen_pixel > 0.0) {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Slide pixels per screen pixel must be positive and finite, got {}",
+                slide_pixels_per_screen_pixel
+            )));
+        }
+        self.get_best_level_for_downsample(slide_pixels_per_screen_pixel)
+    }
+
+    /// Get the integer downsample factor from level `fine` to level `coarse`, if there is one.
+    ///
+    /// This is `Some(k)` if the dimensions of `coarse` are the dimensions of `fine` divided by
+    /// `k` (up to rounding), e.g. to use a box filter between the levels, and `None` otherwise.
+    /// See `utils::integer_downsample`.
+    pub fn integer_downsample_between(
+        &self,
+        fine: u32,
+        coarse: u32,
+    ) -> Result<Option<u32>, OpenSlideError> {
+        if coarse < fine {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Level {} is not coarser than level {}",
+                coarse, fine
+            )));
+        }
+        let fine_dimensions = self.get_level_dimensions(fine)?;
+        let coarse_dimensions = self.get_level_dimensions(coarse)?;
+        Ok(utils::integer_downsample(fine_dimensions, coarse_dimensions))
+    }
+
+    /// Return (new_height, new_width) where
+    ///
+    /// new_height = min(height, max_height)
+    /// new_width = min(width, max_width)
+    ///
+    /// and max_{height, width} are computed based on the top left corner coordinates and the
+    /// dimensions of the image.
+    fn get_feasible_dimensions<
+        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<(u64, u64), OpenSlideError> {
+        let (max_width, max_height) = self.get_level_dimensions(level)?;
+        let downsample_factor = self.get_level_downsample(level)?;
+
+        let tl_row_this_lvl = utils::lvl0_to_level_coordinate(
+            to_u64(top_left_lvl0_row, "top_left_lvl0_row")?,
+            downsample_factor,
+        );
+        let tl_col_this_lvl = utils::lvl0_to_level_coordinate(
+            to_u64(top_left_lvl0_col, "top_left_lvl0_col")?,
+            downsample_factor,
+        );
+
+        let height = to_u64(height, "height")?;
+        let width = to_u64(width, "width")?;
+        let new_height = height.min(max_height - tl_row_this_lvl);
+        let new_width = width.min(max_width - tl_col_this_lvl);
+
+        if new_height < height {
+            println!(
+                "WARNING: Requested region height is changed from {} to {} in order to fit",
+                height, new_height
+            );
+        }
+        if new_width < width {
+            println!(
+                "WARNING: Requested region width is changed from {} to {} in order to fit",
+                width, new_width
+            );
+        }
+
+        if new_height > max_height {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Requested height {} exceeds maximum {}",
+                height, max_height
+            )));
+        }
+
+        if new_width > max_width {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Requested width {} exceeds maximum {}",
+                width, max_width
+            )));
+        }
+
+        Ok((new_height, new_width))
+    }
+
+    /// Copy pre-multiplied ARGB data from a whole slide image.
+    ///
+    /// This function reads and decompresses a region of a whole slide image into an RGBA image
+    ///
+    /// Args:
+    ///     top_left_lvl0_row: Row coordinate (increasing downwards) of top left pixel position
+    ///     top_left_lvl0_col: Column coordinate (increasing to the right) of top left pixel
+    ///                        position
+    ///     level: At which level to grab the region from
+    ///     height: Height in pixels of the outputted region
+    ///     width: Width in pixels of the outputted region
+    ///
+    /// With `OpenSlideBuilder::auto_orient()`, the region is made upright, see there.
+    pub fn read_region<T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy>(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        let image =
+            self.read_region_stored(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        match self.orientation {
+            Some(orientation) if orientation != utils::Orientation::Normal => {
+                Ok(utils::orient(&image, orientation))
+            }
+            _ => Ok(image),
+        }
+    }
+
+    /// Read a region like `read_region`, but always as stored, regardless of `auto_orient`.
+    fn read_region_stored<T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy>(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        let (buffer, height, width) =
+            self.read_region_buffer(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        let word_repr = self.word_representation;
+        let image = utils::decode_buffer(&buffer, height, width, word_repr)?;
+
+        let expected_dimensions = (width as u32, height as u32);
+        debug_assert_eq!(
+            image.dimensions(),
+            expected_dimensions,
+            "Decoded region has (width, height) {:?}, expected {:?}",
+            image.dimensions(),
+            expected_dimensions,
+        );
+        if self.verify_region_dimensions && image.dimensions() != expected_dimensions {
+            return Err(OpenSlideError::OpenSlideInternal(format!(
+                "Decoded region has (width, height) {:?}, but {:?} was requested",
+                image.dimensions(),
+                expected_dimensions,
+            )));
+        }
+
+        Ok(image)
+    }
+
+    /// Read a region as normalized, premultiplied floating point RGBA values in `[0, 1]`.
+    ///
+    /// Premultiplied values are the ones to use for alpha compositing, e.g. of overlays. See
+    /// `utils::decode_buffer_f32()`. The arguments are the same as for `read_region`.
+    pub fn read_region_f32<T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy>(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<ImageBuffer<Rgba<f32>, Vec<f32>>, OpenSlideError> {
+        let (buffer, height, width) =
+            self.read_region_buffer(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        utils::decode_buffer_f32(&buffer, height, width, self.word_representation)
+    }
+
+    /// Read a region as the undecoded, premultiplied ARGB words from OpenSlide.
+    ///
+    /// Every pixel is a native `u32`, `alpha << 24 | red << 16 | green << 8 | blue`, where the
+    /// colors are premultiplied by alpha (so no channel is above alpha). The pixels are in row
+    /// major order, `width` words per row. Like `read_region`, this clips regions extending beyond
+    /// the level to fit; use `read_region_words` to also get the dimensions of the clipped region.
+    /// The arguments are the same as for `read_region`.
+    pub fn read_region_raw<
+        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<Vec<u32>, OpenSlideError> {
+        let (buffer, _, _) =
+            self.read_region_buffer(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        Ok(buffer)
+    }
+
+    /// Read a region as the undecoded words from OpenSlide, with the region width and height.
+    ///
+    /// Every word is a premultiplied ARGB pixel, as described for `utils::WordRepresentation`, in
+    /// row major order. Like `read_region`, this clips regions extending beyond the level to fit,
+    /// so the returned (width, height) can be smaller than requested. The arguments are the same
+    /// as for `read_region`.
+    pub fn read_region_words<
+        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<(Vec<u32>, u32, u32), OpenSlideError> {
+        let (buffer, height, width) =
+            self.read_region_buffer(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        Ok((buffer, to_u32(width, "width")?, to_u32(height, "height")?))
+    }
+
+    /// Read the buffer of a region, returning it with the (height, width) it was read with.
+    ///
+    /// Like `read_region`, this clips regions extending beyond the level to fit.
+    fn read_region_buffer<T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy>(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<(Vec<u32>, u64, u64), OpenSlideError> {
+        let (height, width) = self.get_feasible_dimensions(
+            top_left_lvl0_row,
+            top_left_lvl0_col,
+            level,
+            height,
+            width,
+        )?;
+
+        let row = to_i64(top_left_lvl0_row, "top_left_lvl0_row")?;
+        let col = to_i64(top_left_lvl0_col, "top_left_lvl0_col")?;
+        let level = to_u32(level, "level")?;
+        let buffer = self.read_pixels(row, col, level, height, width)?;
+        self.check_read(&buffer, level, (row, col), (height, width))?;
+        Ok((buffer, height, width))
+    }
+
+    /// Read pixels from OpenSlide, reporting the read to the tile read callback if there is one.
+    fn read_pixels(
+        &self,
+        top_left_lvl0_row: i64,
+        top_left_lvl0_col: i64,
+        level: u32,
+        height: u64,
+        width: u64,
+    ) -> Result<Vec<u32>, OpenSlideError> {
+        let start = self.tile_read_callback.as_ref().map(|_| Instant::now());
+        let buffer = unsafe {
+            bindings::read_region(
+                self.osr.0,
+                top_left_lvl0_col,
+                top_left_lvl0_row,
+                to_i32(level, "level")?,
+                to_i64(width, "width")?,
+                to_i64(height, "height")?,
+            )?
+        };
+        if let (Some(callback), Some(start)) = (&self.tile_read_callback, start) {
+            (callback.0)(TileReadEvent {
+                level,
+                top_left_lvl0: (top_left_lvl0_row, top_left_lvl0_col),
+                size: (height, width),
+                duration: start.elapsed(),
+            });
+        }
+        Ok(buffer)
+    }
+
+    /// Read a region with a top left corner that can lie above or left of the slide.
+    ///
+    /// Unlike in `read_region`, the level 0 (row, column) of the top left corner can be negative,
+    /// and the region is never clipped. Pixels outside of the slide are transparent, as read by
+    /// OpenSlide. This is e.g. for regions centered on a point near the edge of the slide.
+    pub fn read_region_signed(
+        &self,
+        top_left_lvl0: (i64, i64),
+        level: u32,
+        size: (u32, u32),
+    ) -> Result<RgbaImage, OpenSlideError> {
+        let (top_left_row, top_left_col) = top_left_lvl0;
+        let (height, width) = size;
+        let buffer = self.read_pixels(
+            top_left_row,
+            top_left_col,
+            level,
+            u64::from(height),
+            u64::from(width),
+        )?;
+        self.check_read(
+            &buffer,
+            level,
+            top_left_lvl0,
+            (u64::from(height), u64::from(width)),
+        )?;
+        utils::decode_buffer(&buffer, height, width, self.word_representation)
+    }
+
+    /// Read a region, returning an error if it extends beyond the level.
+    ///
+    /// The top left corner is converted to level coordinates with the downsample factor of the
+    /// level, and the region must then lie within the dimensions of the level. Unlike
+    /// `read_region`, which clips regions extending beyond the level (and OpenSlide, which reads
+    /// them as transparent pixels), the region is never changed. The arguments are the same as
+    /// for `read_region`.
+    pub fn read_region_checked<
+        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        let (level_width, level_height) = self.get_level_dimensions(level)?;
+        let downsample_factor = self.get_level_downsample(level)?;
+        let row = utils::lvl0_to_level_coordinate(
+            to_u64(top_left_lvl0_row, "top_left_lvl0_row")?,
+            downsample_factor,
+        );
+        let col = utils::lvl0_to_level_coordinate(
+            to_u64(top_left_lvl0_col, "top_left_lvl0_col")?,
+            downsample_factor,
+        );
+        let bottom = row.checked_add(to_u64(height, "height")?);
+        let right = col.checked_add(to_u64(width, "width")?);
+        let inside = match (bottom, right) {
+            (Some(bottom), Some(right)) => bottom <= level_height && right <= level_width,
+            _ => false,
+        };
+        if !inside {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Region with top left (row {}, col {}) and (height, width) ({}, {}) at \
+                 level {} extends beyond the level, which has (height, width) ({}, {})",
+                row, col, height, width, level, level_height, level_width
+            )));
+        }
+        self.read_region_stored(top_left_lvl0_row, top_left_lvl0_col, level, height, width)
+    }
+
+    /// Read a region, along with the level 0 rectangle it covers.
+    ///
+    /// Like `read_region`, this clips regions extending beyond the level to fit, so the rectangle
+    /// can be smaller than the requested region. The arguments are the same as for `read_region`.
+    pub fn read_region_clipped<
+        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<ReadRegion, OpenSlideError> {
+        let image =
+            self.read_region_stored(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        let level = to_u32(level, "level")?;
+        let downsample_factor = self.get_level_downsample(level)?;
+        let level0_rect = (
+            to_u64(top_left_lvl0_row, "top_left_lvl0_row")?,
+            to_u64(top_left_lvl0_col, "top_left_lvl0_col")?,
+            utils::level_to_lvl0_coordinate(u64::from(image.height()), downsample_factor),
+            utils::level_to_lvl0_coordinate(u64::from(image.width()), downsample_factor),
+        );
+        Ok(ReadRegion {
+            image,
+            level0_rect,
+            level,
+        })
+    }
+
+    /// Read a region as an Rgb image and a separate grayscale alpha mask.
+    ///
+    /// The colors are un-premultiplied, as in `read_region`. The arguments are the same as for
+    /// `read_region`.
+    pub fn read_region_split_alpha<
+        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<(RgbImage, GrayImage), OpenSlideError> {
+        let region =
+            self.read_region_stored(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        Ok(utils::split_alpha(&region))
+    }
+
+    /// Read a region as an Rgb image, composited over the background color of the slide.
+    ///
+    /// The colors are un-premultiplied, as in `read_region`, and composited over the background
+    /// color (`openslide.background-color`, white if the slide does not have one) before the
+    /// alpha channel is dropped. Opaque pixels keep their color, and partially transparent pixels
+    /// (e.g. at the edge of the scanned area) blend into the background. The arguments are the
+    /// same as for `read_region`.
+    pub fn read_region_rgb<T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy>(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<RgbImage, OpenSlideError> {
+        let background = self.background_color()?;
+        let region =
+            self.read_region_stored(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        Ok(utils::composite_over(&region, background))
+    }
+
+    /// Read a region as a grayscale image.
+    ///
+    /// Before the luminance is computed, pixels are composited over the background color of the
+    /// slide (`openslide.background-color`, white if the slide does not have one), so that
+    /// transparent pixels, e.g. outside the scanned area, get a background luminance rather than
+    /// black. Fully transparent pixels can instead be given a sentinel value, see
+    /// `utils::TransparentGray`. The other arguments are the same as for `read_region`.
+    pub fn read_region_gray<T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy>(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+        transparent: utils::TransparentGray,
+    ) -> Result<GrayImage, OpenSlideError> {
+        let background = self.background_color()?;
+        let region =
+            self.read_region_stored(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        Ok(utils::rgba_to_gray(&region, background, transparent))
+    }
+
+    /// Read a region as an 8 bit grayscale image, with `utils::decode_buffer_luma()`.
+    ///
+    /// This skips the Rgba image of `read_region`, but drops alpha, so transparent pixels are
+    /// black. Use `read_region_gray` to composite them over the background instead. The
+    /// arguments are the same as for `read_region`.
+    pub fn read_region_luma<
+        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+    ) -> Result<GrayImage, OpenSlideError> {
+        let (buffer, height, width) =
+            self.read_region_buffer(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        utils::decode_buffer_luma(&buffer, height, width, self.word_representation)
+    }
+
+    /// Read a region as flat floating point RGB data in a tensor layout, along with its shape.
+    ///
+    /// The region is given by its top left corner (row, column) at level 0, and its (height,
+    /// width) at `level`, and is clipped to the level like in `read_region`. See
+    /// `utils::decode_buffer_tensor()` for the layout and the normalization.
+    #[cfg(feature = "tensor")]
+    pub fn read_region_tensor(
+        &self,
+        top_left_lvl0: (u64, u64),
+        level: u32,
+        size: (u64, u64),
+        layout: utils::TensorLayout,
+        normalize: Option<(f32, f32)>,
+    ) -> Result<(Vec<f32>, [usize; 4]), OpenSlideError> {
+        let (top_left_row, top_left_col) = top_left_lvl0;
+        let (height, width) = size;
+        let level = u64::from(level);
+        let (buffer, height, width) =
+            self.read_region_buffer(top_left_row, top_left_col, level, height, width)?;
+        let word_repr = self.word_representation;
+        utils::decode_buffer_tensor(&buffer, height, width, word_repr, layout, normalize)
+    }
+
+    /// Read a region with a gamma curve applied to the color channels.
+    ///
+    /// The curve is applied with a lookup table, see `utils::gamma_lut()`. The other arguments
+    /// are the same as for `read_region`.
+    pub fn read_region_gamma<
+        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+        gamma: f32,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        // Check the gamma before reading
+        utils::gamma_lut(gamma)?;
+        let mut region =
+            self.read_region_stored(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        utils::apply_gamma(&mut region, gamma)?;
+        Ok(region)
+    }
+
+    /// Read a region and separate it into the images of three stains.
+    ///
+    /// The region is composited over the background color of the slide, and separated with
+    /// `utils::color_deconvolve()`, e.g. with `utils::HE_STAIN_MATRIX`. The other arguments are
+    /// the same as for `read_region`.
+    pub fn read_region_stains<
+        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+        stain_matrix: [[f32; 3]; 3],
+    ) -> Result<[GrayImage; 3], OpenSlideError> {
+        let background = self.background_color()?;
+        let region =
+            self.read_region_stored(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        utils::color_deconvolve(&utils::composite_over(&region, background), stain_matrix)
+    }
+
+    /// Read a region as NV12, returning the (Y plane, interleaved UV plane).
+    ///
+    /// See `utils::rgba_to_nv12()` for the layout. The other arguments are the same as for
+    /// `read_region`.
+    pub fn read_region_nv12<T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy>(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+        matrix: utils::YuvMatrix,
+    ) -> Result<(Vec<u8>, Vec<u8>), OpenSlideError> {
+        let region =
+            self.read_region_stored(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        Ok(utils::rgba_to_nv12(&region, matrix))
+    }
+
+    /// Read a region and encode it as a data URI, e.g. `data:image/png;base64,...`.
+    ///
+    /// See `utils::to_data_uri()` for the supported formats. The other arguments are the same as
+    /// for `read_region`.
+    pub fn read_region_data_uri<
+        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+        format: ImageFormat,
+    ) -> Result<String, OpenSlideError> {
+        let region =
+            self.read_region_stored(top_left_lvl0_row, top_left_lvl0_col, level, height, width)?;
+        utils::to_data_uri(&region, format)
+    }
+
+    /// Copy pre-multiplied ARGB data from a whole slide image, tile by tile, with cancellation.
+    ///
+    /// This reads the same region as `read_region`, but streams it in tiles (using the tile size
+    /// of the level when known), and checks `cancel` before every tile. If `cancel` is set, the
+    /// read stops and an error is returned.
+    ///
+    /// A single read from OpenSlide can not be interrupted, so the cancellation granularity is
+    /// one tile: a read that is in progress when `cancel` is set finishes before the error is
+    /// returned.
+    pub fn read_region_cancelable<
+        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+        cancel: &AtomicBool,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        let (height, width) = self.get_feasible_dimensions(
+            top_left_lvl0_row,
+            top_left_lvl0_col,
+            level,
+            height,
+            width,
+        )?;
+        let top_left_lvl0_row = to_u64(top_left_lvl0_row, "top_left_lvl0_row")?;
+        let top_left_lvl0_col = to_u64(top_left_lvl0_col, "top_left_lvl0_col")?;
+        let level = to_u32(level, "level")?;
+        let downsample_factor = self.get_level_downsample(level)?;
+        let (tile_height, tile_width) = self.streaming_tile_size(level);
+
+        let mut image = RgbaImage::new(width as u32, height as u32);
+        for tile_row in (0..height).step_by(tile_height as usize) {
+            for tile_col in (0..width).step_by(tile_width as usize) {
+                if cancel.load(Ordering::Relaxed) {
+                    return Err(OpenSlideError::Cancelled(format!(
+                        "Region read was cancelled at tile (row {}, col {})",
+                        tile_row, tile_col,
+                    )));
+                }
+                let this_height = tile_height.min(height - tile_row);
+                let this_width = tile_width.min(width - tile_col);
+                let lvl0_row = top_left_lvl0_row
+                    + utils::level_to_lvl0_coordinate(tile_row, downsample_factor);
+                let lvl0_col = top_left_lvl0_col
+                    + utils::level_to_lvl0_coordinate(tile_col, downsample_factor);
+
+                let lvl0_row = to_i64(lvl0_row, "top_left_lvl0_row")?;
+                let lvl0_col = to_i64(lvl0_col, "top_left_lvl0_col")?;
+                let buffer =
+                    self.read_pixels(lvl0_row, lvl0_col, level, this_height, this_width)?;
+                self.check_read(
+                    &buffer,
+                    level,
+                    (lvl0_row, lvl0_col),
+                    (this_height, this_width),
+                )?;
+                let word_repr = self.word_representation;
+                let tile = utils::decode_buffer(&buffer, this_height, this_width, word_repr)?;
+                imageops::replace(&mut image, &tile, tile_col as u32, tile_row as u32);
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// The (height, width) of the tiles used when streaming a level.
+    ///
+    /// This is the tile size of the level as stated in the slide properties, or 512 for a side
+    /// the slide does not state, or states as 0.
+    fn streaming_tile_size(&self, level: u32) -> (u64, u64) {
+        let side = |size: Option<u32>| u64::from(size.filter(|&size| size > 0).unwrap_or(512));
+        let level_properties = self
+            .properties
+            .levels()
+            .and_then(|levels| levels.get(level as usize).cloned());
+        match level_properties {
+            Some(properties) => (
+                side(properties.tile_height()),
+                side(properties.tile_width()),
+            ),
+            None => (512, 512),
+        }
+    }
+
+    /// Read a region and downsample it by an integer factor with a box filter.
+    ///
+    /// This reads a region of `factor * height` x `factor * width` pixels at the given level, and
+    /// averages every `factor` x `factor` block into one output pixel. For power-of-two factors,
+    /// this is both faster and free of the artifacts from general resampling.
+    ///
+    /// Args:
+    ///     top_left_lvl0_row: Row coordinate (increasing downwards) of top left pixel position
+    ///     top_left_lvl0_col: Column coordinate (increasing to the right) of top left pixel
+    ///                        position
+    ///     level: At which level to grab the region from
+    ///     height: Height in pixels of the outputted region
+    ///     width: Width in pixels of the outputted region
+    ///     factor: Downsample factor applied to the region read at `level`
+    pub fn read_region_box_downsampled<
+        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
+    >(
+        &self,
+        top_left_lvl0_row: T,
+        top_left_lvl0_col: T,
+        level: T,
+        height: T,
+        width: T,
+        factor: u32,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        if factor == 0 {
+            return Err(OpenSlideError::InvalidArgument(String::from(
+                "Box filter factor must be positive, got 0",
+            )));
+        }
+        let factor_u64 = u64::from(factor);
+
+        let region = self.read_region_stored(
+            to_u64(top_left_lvl0_row, "top_left_lvl0_row")?,
+            to_u64(top_left_lvl0_col, "top_left_lvl0_col")?,
+            to_u64(level, "level")?,
+            to_u64(height, "height")? * factor_u64,
+            to_u64(width, "width")? * factor_u64,
+        )?;
+        utils::box_downsample(&region, factor)
+    }
+
+    /// Read a region at exactly half of the level 0 resolution.
+    ///
+    /// The region is given by its top left corner (row, column) and (height, width) at level 0,
+    /// and the result is (height / 2, width / 2), rounded down. It is box downsampled from the
+    /// coarsest level with a downsample that divides 2, see `read_region_box_downsampled`.
+    pub fn read_region_half(
+        &self,
+        top_left_lvl0: (u64, u64),
+        size_lvl0: (u64, u64),
+    ) -> Result<RgbaImage, OpenSlideError> {
+        self.read_region_fraction(top_left_lvl0, size_lvl0, 2)
+    }
+
+    /// Read a region at exactly a quarter of the level 0 resolution.
+    ///
+    /// This is the same as `read_region_half`, but for a quarter (e.g. a level with downsample 4
+    /// is read as is, and level 0 is box downsampled by 4).
+    pub fn read_region_quarter(
+        &self,
+        top_left_lvl0: (u64, u64),
+        size_lvl0: (u64, u64),
+    ) -> Result<RgbaImage, OpenSlideError> {
+        self.read_region_fraction(top_left_lvl0, size_lvl0, 4)
+    }
+
+    /// Read a region at exactly `1 / fraction` of the level 0 resolution.
+    fn read_region_fraction(
+        &self,
+        top_left_lvl0: (u64, u64),
+        size_lvl0: (u64, u64),
+        fraction: u32,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        let (top_left_row, top_left_col) = top_left_lvl0;
+        let (height, width) = size_lvl0;
+
+        let mut level = 0;
+        let mut factor = fraction;
+        for candidate in (1..self.get_level_count()?).rev() {
+            let ratio = f64::from(fraction) / self.get_level_downsample(candidate)?;
+            let rounded = ratio.round();
+            if rounded >= 1.0 && (ratio - rounded).abs() <= rounded * utils::DOWNSAMPLE_TOLERANCE {
+                level = candidate;
+                factor = rounded as u32;
+                break;
+            }
+        }
+
+        self.read_region_box_downsampled(
+            top_left_row,
+            top_left_col,
+            u64::from(level),
+            height / u64::from(fraction),
+            width / u64::from(fraction),
+            factor,
+        )
+    }
+
+    /// Read the detail that a fine level has over a coarse level, e.g. for unsharp masking.
+    ///
+    /// The region is given by its top left corner (row, column) at level 0, and its (height,
+    /// width) at `fine_level`. The same area is read from `coarse_level`, upsampled to the size of
+    /// the fine region, and subtracted from it with `utils::difference_image()`.
+    pub fn read_region_detail(
+        &self,
+        top_left_lvl0: (u64, u64),
+        size: (u64, u64),
+        fine_level: u32,
+        coarse_level: u32,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        let fine_downsample = self.get_level_downsample(fine_level)?;
+        let coarse_downsample = self.get_level_downsample(coarse_level)?;
+        if coarse_downsample < fine_downsample {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Level {} with downsample {} is not coarser than level {} with \
+                 downsample {}",
+                coarse_level, coarse_downsample, fine_level, fine_downsample
+            )));
+        }
+
+        let (top_left_row, top_left_col) = top_left_lvl0;
+        let (height, width) = size;
+        let fine_level = u64::from(fine_level);
+        let fine =
+            self.read_region_stored(top_left_row, top_left_col, fine_level, height, width)?;
+
+        let (fine_width, fine_height) = fine.dimensions();
+        let lvl0_height = utils::level_to_lvl0_coordinate(u64::from(fine_height), fine_downsample);
+        let lvl0_width = utils::level_to_lvl0_coordinate(u64::from(fine_width), fine_downsample);
+        let coarse = self.read_region_stored(
+            top_left_row,
+            top_left_col,
+            u64::from(coarse_level),
+            utils::lvl0_to_level_coordinate(lvl0_height, coarse_downsample).max(1),
+            utils::lvl0_to_level_coordinate(lvl0_width, coarse_downsample).max(1),
+        )?;
+        let coarse = if coarse.dimensions() == fine.dimensions() {
+            coarse
+        } else {
+            imageops::resize(&coarse, fine_width, fine_height, FilterType::Triangle)
+        };
+        utils::difference_image(&fine, &coarse)
+    }
+
+    /// Read a region at an arbitrary downsample, blending the two levels that bracket it.
+    ///
+    /// The region is given by its top left corner (row, column) at level 0, and its (height,
+    /// width) in the output, which covers `out_size * downsample` pixels of level 0. It is read
+    /// from the best level for `downsample` and from the next coarser level, both are resized to
+    /// `out_size`, and they are blended linearly by where `downsample` lies between the
+    /// downsamples of the two levels (see `utils::blend()`). Changing `downsample` continuously
+    /// then gives a smooth zoom, without popping when the best level changes. Below the
+    /// downsample of level 0 and above that of the coarsest level, only that level is used.
+    pub fn read_region_trilinear(
+        &self,
+        top_left_lvl0: (u64, u64),
+        out_size: (u32, u32),
+        downsample: f64,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        let (out_height, out_width) = out_size;
+        if out_height == 0 || out_width == 0 || downsample.is_nan() || downsample <= 0.0 {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Output (height, width) and downsample must be positive, got ({}, {}) \
+                 and {}",
+                out_height, out_width, downsample
+            )));
+        }
+
+        let fine_level = self.get_best_level_for_downsample(downsample)?;
+        let fine_downsample = self.get_level_downsample(fine_level)?;
+        let fine = self.read_region_resized(top_left_lvl0, out_size, downsample, fine_level)?;
+        if fine_level + 1 >= self.get_level_count()? {
+            return Ok(fine);
+        }
+
+        let coarse_level = fine_level + 1;
+        let coarse_downsample = self.get_level_downsample(coarse_level)?;
+        let weight = ((downsample - fine_downsample) / (coarse_downsample - fine_downsample))
+            .clamp(0.0, 1.0);
+        if weight == 0.0 {
+            return Ok(fine);
+        }
+        let coarse = self.read_region_resized(top_left_lvl0, out_size, downsample, coarse_level)?;
+        utils::blend(&fine, &coarse, weight)
+    }
+
+    /// Read the region of `read_region_trilinear` from a single level, resized to `out_size`.
+    fn read_region_resized(
+        &self,
+        top_left_lvl0: (u64, u64),
+        out_size: (u32, u32),
+        downsample: f64,
+        level: u32,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        let (top_left_row, top_left_col) = top_left_lvl0;
+        let (out_height, out_width) = out_size;
+        let level_downsample = self.get_level_downsample(level)?;
+        let scale = downsample / level_downsample;
+        let region = self.read_region_stored(
+            top_left_row,
+            top_left_col,
+            u64::from(level),
+            ((f64::from(out_height) * scale).round() as u64).max(1),
+            ((f64::from(out_width) * scale).round() as u64).max(1),
+        )?;
+        if region.dimensions() == (out_width, out_height) {
+            return Ok(region);
+        }
+        Ok(imageops::resize(&region, out_width, out_height, FilterType::Triangle))
+    }
+
+    /// Read a region of interest at level 0 resolution, as a lazy sequence of tiles.
+    ///
+    /// The region of interest is given as (top row, left column, height, width) in level 0
+    /// coordinates, and must lie within level 0. It is covered by tiles of `tile_size` x
+    /// `tile_size` pixels, in row major order, where the tiles at the bottom and right edges are
+    /// cut to the region. Every item is the (row, column) of the tile in the grid of tiles, and
+    /// the tile itself. A tile is only read when its item is requested.
+    pub fn read_roi_tiled<'a>(
+        &'a self,
+        roi_lvl0: (u64, u64, u64, u64),
+        tile_size: u32,
+    ) -> Result<
+        impl Iterator<Item = Result<(u32, u32, RgbaImage), OpenSlideError>> + 'a,
+        OpenSlideError,
+    > {
+        let (top_row, left_col, height, width) = roi_lvl0;
+        if tile_size == 0 {
+            return Err(OpenSlideError::InvalidArgument(String::from(
+                "Tile size must be positive, got 0",
+            )));
+        }
+        let (max_width, max_height) = self.get_level0_dimensions()?;
+        if top_row + height > max_height || left_col + width > max_width {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Region of interest (row, col, height, width) {:?} extends beyond level 0 \
+                 with (width, height) ({}, {})",
+                roi_lvl0, max_width, max_height
+            )));
+        }
+
+        let tile_size = u64::from(tile_size);
+        let num_tile_rows = height.div_ceil(tile_size);
+        let num_tile_cols = width.div_ceil(tile_size);
+        let tiles = (0..num_tile_rows)
+            .flat_map(move |tile_row| (0..num_tile_cols).map(move |tile_col| (tile_row, tile_col)));
+        Ok(tiles.map(move |(tile_row, tile_col)| {
+            let row = tile_row * tile_size;
+            let col = tile_col * tile_size;
+            let tile = self.read_region_stored(
+                top_row + row,
+                left_col + col,
+                0,
+                tile_size.min(height - row),
+                tile_size.min(width - col),
+            )?;
+            Ok((tile_row as u32, tile_col as u32, tile))
+        }))
+    }
+
+    /// Read a whole level as a lazy sequence of non-overlapping tiles.
+    ///
+    /// The level is covered by tiles of `tile_size` (height, width) pixels, in row major order,
+    /// where the tiles at the bottom and right edges are cut to the level. The position of every
+    /// tile in level 0 coordinates is computed with the downsample factor of the level.
+    pub fn tiles<'a>(
+        &'a self,
+        level: u32,
+        tile_size: (u32, u32),
+    ) -> Result<TileIterator<'a>, OpenSlideError> {
+        let (tile_height, tile_width) = tile_size;
+        if tile_height == 0 || tile_width == 0 {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Tile size must be positive, got (height, width) {:?}",
+                tile_size
+            )));
+        }
+        let (width, height) = self.get_level_dimensions(level)?;
+        let downsample_factor = self.get_level_downsample(level)?;
+        let tile_size = (u64::from(tile_height), u64::from(tile_width));
+        Ok(TileIterator {
+            slide: self,
+            level,
+            downsample_factor,
+            level_size: (height, width),
+            tile_size,
+            grid_size: (height.div_ceil(tile_size.0), width.div_ceil(tile_size.1)),
+            next_tile: 0,
+        })
+    }
+
+    /// Read a region with a height and width that are multiples of `multiple`.
+    ///
+    /// The region is given by its top left corner (row, column) at level 0, and its approximate
+    /// (height, width) at `level`. The size is first clipped to the level, like in `read_region`,
+    /// and then rounded down to the nearest multiples of `multiple`. Returns an error if a
+    /// dimension rounds down to zero.
+    pub fn read_region_aligned(
+        &self,
+        top_left_lvl0: (u64, u64),
+        approx_size: (u64, u64),
+        level: u32,
+        multiple: u32,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        if multiple == 0 {
+            return Err(OpenSlideError::InvalidArgument(String::from(
+                "Multiple must be positive, got 0",
+            )));
+        }
+        let (top_left_row, top_left_col) = top_left_lvl0;
+        let (height, width) = approx_size;
+        let level = u64::from(level);
+        let (height, width) =
+            self.get_feasible_dimensions(top_left_row, top_left_col, level, height, width)?;
+
+        let multiple = u64::from(multiple);
+        let aligned_height = height - height % multiple;
+        let aligned_width = width - width % multiple;
+        if aligned_height == 0 || aligned_width == 0 {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Region (height, width) ({}, {}) is smaller than the multiple {}",
+                height, width, multiple
+            )));
+        }
+        self.read_region_stored(
+            top_left_row,
+            top_left_col,
+            level,
+            aligned_height,
+            aligned_width,
+        )
+    }
+
+    /// Read a region of size `out_size` (height, width) at `level`, centered on the slide.
+    ///
+    /// The size is clamped to the dimensions of the level, so the region never extends beyond
+    /// the slide.
+    pub fn center_crop(
+        &self,
+        out_size: (u32, u32),
+        level: u32,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        let (level_width, level_height) = self.get_level_dimensions(level)?;
+        let height = u64::from(out_size.0).min(level_height);
+        let width = u64::from(out_size.1).min(level_width);
+        let downsample_factor = self.get_level_downsample(level)?;
+        let top_left_row =
+            utils::level_to_lvl0_coordinate((level_height - height) / 2, downsample_factor);
+        let top_left_col =
+            utils::level_to_lvl0_coordinate((level_width - width) / 2, downsample_factor);
+        self.read_region_stored(top_left_row, top_left_col, u64::from(level), height, width)
+    }
+
+    /// Read a window around a point at the most detailed level that fits in a pixel budget.
+    ///
+    /// The window has the (height, width) `window_lvl0` in level 0 coordinates, is centered on
+    /// `center_lvl0` (row, column in level 0 coordinates), and is shifted or shrunk to stay within
+    /// the slide. It is read from level 0 if it has at most `max_pixels` pixels there, else from
+    /// the most detailed (i.e. the first) level at which it does. Returns an error if the window
+    /// does not fit in the budget at any level.
+    ///
+    /// Returns the region along with its level and the level 0 rectangle it covers.
+    pub fn read_max_detail_around(
+        &self,
+        center_lvl0: (u64, u64),
+        window_lvl0: (u64, u64),
+        max_pixels: u64,
+    ) -> Result<ReadRegion, OpenSlideError> {
+        let (center_row, center_col) = center_lvl0;
+        let (width, height) = self.get_level0_dimensions()?;
+        if center_row >= height || center_col >= width {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Center (row {}, column {}) is outside the slide, which has \
+                 (height, width) ({}, {})",
+                center_row, center_col, height, width
+            )));
+        }
+        if window_lvl0.0 == 0 || window_lvl0.1 == 0 {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Window (height, width) {:?} is empty",
+                window_lvl0
+            )));
+        }
+
+        let window_height = window_lvl0.0.min(height);
+        let window_width = window_lvl0.1.min(width);
+        let top_left_row = center_row
+            .saturating_sub(window_height / 2)
+            .min(height - window_height);
+        let top_left_col = center_col
+            .saturating_sub(window_width / 2)
+            .min(width - window_width);
+
+        for level in 0..self.get_level_count()? {
+            let downsample_factor = self.get_level_downsample(level)?;
+            let region_height = ((window_height as f64 / downsample_factor) as u64).max(1);
+            let region_width = ((window_width as f64 / downsample_factor) as u64).max(1);
+            if region_height * region_width <= max_pixels {
+                return self.read_region_clipped(
+                    top_left_row,
+                    top_left_col,
+                    u64::from(level),
+                    region_height,
+                    region_width,
+                );
+            }
+        }
+        Err(OpenSlideError::InvalidArgument(format!(
+            "Window (height, width) ({}, {}) does not fit in a pixel budget of {} at any level",
+            window_height, window_width, max_pixels
+        )))
+    }
+
+    /// Read several regions and draw them onto a single canvas.
+    ///
+    /// `canvas_size` is the (height, width) of the canvas, and `placements` holds the (row,
+    /// column) on the canvas of the top left corner of each region. Regions are drawn in order,
+    /// so where they overlap the last one wins. Parts of regions falling outside the canvas are
+    /// clipped, and canvas pixels not covered by any region are transparent.
+    pub fn stitch_regions(
+        &self,
+        regions: &[Region],
+        canvas_size: (u32, u32),
+        placements: &[(u32, u32)],
+    ) -> Result<RgbaImage, OpenSlideError> {
+        if regions.len() != placements.len() {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Got {} regions but {} placements",
+                regions.len(),
+                placements.len()
+            )));
+        }
+
+        let (canvas_height, canvas_width) = canvas_size;
+        let mut canvas = RgbaImage::new(canvas_width, canvas_height);
+        for (region, &(row, col)) in regions.iter().zip(placements) {
+            if row >= canvas_height || col >= canvas_width {
+                continue;
+            }
+            let image = self.read_region_stored(
+                region.top_left_lvl0_row,
+                region.top_left_lvl0_col,
+                u64::from(region.level),
+                region.height,
+                region.width,
+            )?;
+            imageops::replace(&mut canvas, &image, col, row);
+        }
+        Ok(canvas)
+    }
+
+    /// Read many regions in parallel, with a pool of `pool_size` handles to the slide.
+    ///
+    /// The regions are split into `pool_size` chunks (0 is taken as 1), and every chunk is read on
+    /// a rayon thread with a handle of its own, opened like with `reopen`. OpenSlide handles can be
+    /// shared between threads, but an error makes a handle unusable for every later read, so with
+    /// handles of their own a failed read only affects the rest of its chunk. Opening a handle can
+    /// be expensive, so the pool size is a trade off between the number of concurrent reads and
+    /// the cost of opening the handles; the number of threads of the rayon pool is a sensible
+    /// choice.
+    ///
+    /// The results are in the order of `regions`, and are the same as from `read_region`.
+    #[cfg(feature = "rayon")]
+    pub fn read_regions_par(
+        &self,
+        regions: &[Region],
+        pool_size: usize,
+    ) -> Vec<Result<RgbaImage, OpenSlideError>> {
+        if regions.is_empty() {
+            return Vec::new();
+        }
+        let chunk_size = regions.len().div_ceil(pool_size.max(1));
+        let builder = self.builder();
+
+        let chunks: Vec<Vec<Result<RgbaImage, OpenSlideError>>> = regions
+            .par_chunks(chunk_size)
+            .map(|chunk| {
+                let slide = match builder.clone().open() {
+                    Ok(slide) => slide,
+                    Err(err) => {
+                        let msg = format!("Unable to open a handle for a parallel read: {}", err);
+                        return chunk
+                            .iter()
+                            .map(|_| Err(OpenSlideError::OpenFailed(msg.clone())))
+                            .collect();
+                    }
+                };
+                chunk
+                    .iter()
+                    .map(|region| {
+                        slide.read_region(
+                            region.top_left_lvl0_row,
+                            region.top_left_lvl0_col,
+                            u64::from(region.level),
+                            region.height,
+                            region.width,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        chunks.into_iter().flatten().collect()
+    }
+
+    /// Check whether a region is background, from a small downsampled proxy of it.
+    ///
+    /// The region is given by its top left corner (row, column) at level 0, and its (height,
+    /// width) at `level`. Instead of the region itself, about 8 x 8 pixels covering it are read
+    /// from the coarsest suitable level, and the region is blank if their mean saturation is below
+    /// `threshold` (see `utils::saturation`). Transparent pixels count as background.
+    ///
+    /// The proxy is only cheap to read if the slide has coarser levels than `level`.
+    pub fn is_region_blank(
+        &self,
+        top_left_lvl0: (u64, u64),
+        size: (u64, u64),
+        level: u32,
+        threshold: f64,
+    ) -> Result<bool, OpenSlideError> {
+        let (top_left_row, top_left_col) = top_left_lvl0;
+        let (height, width) = size;
+        if height == 0 || width == 0 {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Region (height, width) must be positive, got ({}, {})",
+                height, width
+            )));
+        }
+
+        let downsample_factor = self.get_level_downsample(level)?;
+        let lvl0_height = utils::level_to_lvl0_coordinate(height, downsample_factor);
+        let lvl0_width = utils::level_to_lvl0_coordinate(width, downsample_factor);
+        let proxy_downsample = lvl0_height.max(lvl0_width) as f64 / BLANK_PROXY_SIZE as f64;
+        let proxy_level = self.get_best_level_for_downsample(proxy_downsample)?.max(level);
+        let proxy_downsample = self.get_level_downsample(proxy_level)?;
+        let proxy = self.read_region_stored(
+            top_left_row,
+            top_left_col,
+            u64::from(proxy_level),
+            utils::lvl0_to_level_coordinate(lvl0_height, proxy_downsample).max(1),
+            utils::lvl0_to_level_coordinate(lvl0_width, proxy_downsample).max(1),
+        )?;
+
+        let num_pixels = u64::from(proxy.width()) * u64::from(proxy.height());
+        if num_pixels == 0 {
+            return Ok(true);
+        }
+        let total_saturation: f64 = proxy.pixels().map(utils::saturation).sum();
+        Ok(total_saturation / (num_pixels as f64) < threshold)
+    }
+
+    /// Get a dictionary of properties associated with the current slide
+    ///
+    /// There are some standard properties to every slide, but also a lot of vendor-specific
+    /// properties. This method returns a HashMap with all key-value pairs of the properties
+    /// associated with the slide.
+    pub fn get_properties(&self) -> Result<HashMap<String, String>, OpenSlideError> {
+        let mut properties = HashMap::<String, String>::new();
+        for name in unsafe { bindings::get_property_names(self.osr.0)? } {
+            properties.insert(name.clone(), unsafe {
+                bindings::get_property_value(self.osr.0, &name)?
+            });
+        }
+        Ok(properties)
+    }
+
+    /// Get the properties of the slide parsed into typed values
+    ///
+    /// The property map from `get_properties` is read once, and each key is parsed by the parser
+    /// of its prefix (`openslide`, `tiff`, or a vendor like `aperio` or `hamamatsu`). The vendor
+    /// groups that the slide has no keys for are `None`, see e.g. `Properties::aperio()`.
+    pub fn parsed_properties(&self) -> Result<properties::Properties, OpenSlideError> {
+        properties::Properties::new(&self.get_properties()?)
+    }
+
+
+    /// Get the number of properties associated with the current slide
+    ///
+    /// Only the property names are fetched from OpenSlide, not the values.
+    pub fn property_count(&self) -> Result<usize, OpenSlideError> {
+        let names = unsafe { bindings::get_property_names(self.osr.0)? };
+        Ok(names.len())
+    }
+
+    /// Get the names of the associated images (e.g. label, macro and thumbnail) of the slide
+    pub fn get_associated_image_names(&self) -> Result<Vec<String>, OpenSlideError> {
+        let names = unsafe { bindings::get_associated_image_names(self.osr.0)? };
+        Ok(names)
+    }
+
+    /// Get the dimensions of the associated image with the given name.
+    ///
+    /// This method returns the (width, height) number of pixels of the associated image. Returns
+    /// an error if the slide has no associated image with the name.
+    pub fn get_associated_image_dimensions(
+        &self,
+        name: &str,
+    ) -> Result<(u64, u64), OpenSlideError> {
+        let (width, height) =
+            unsafe { bindings::get_associated_image_dimensions(self.osr.0, name)? };
+
+        if width < -1 {
+            return Err(self.openslide_error(format!(
+                "Width is {}, this is an unknown error from OpenSlide. \
+                 OpenSlide returns -1 if an error occured. \
+                 See OpenSlide C API documentation.",
+                width
+            )));
+        } else if width == -1 {
+            return Err(self.openslide_error(format!(
+                "Width is -1, this is a known error from OpenSlide. \
+                 OpenSlide returns -1 if an error occured, e.g. if the slide has no \
+                 associated image {}. See OpenSlide C API documentation.",
+                name
+            )));
+        }
+
+        if height < -1 {
+            return Err(self.openslide_error(format!(
+                "Height is {}, this is an unknown error from OpenSlide. \
+                 OpenSlide returns -1 if an error occured. \
+                 See OpenSlide C API documentation.",
+                height
+            )));
+        } else if height == -1 {
+            return Err(self.openslide_error(format!(
+                "Height is -1, this is a known error from OpenSlide. \
+                 OpenSlide returns -1 if an error occured, e.g. if the slide has no \
+                 associated image {}. See OpenSlide C API documentation.",
+                name
+            )));
+        }
+
+        Ok((width as u64, height as u64))
+    }
+
+    /// Get the name and version of the software that created the slide, if it is recorded.
+    ///
+    /// This is the `tiff.Software` property when present. Otherwise, a vendor specific property
+    /// is used, based on the detected vendor:
+    ///
+    ///   - aperio: The first line of `tiff.ImageDescription` (e.g. "Aperio Image Library v11.2.1")
+    ///   - leica: `leica.device-version`
+    ///   - philips: `philips.DICOM_SOFTWARE_VERSIONS`
+    pub fn creating_software(&self) -> Result<Option<String>, OpenSlideError> {
+        let properties = self.get_properties()?;
+        if let Some(software) = properties.get("tiff.Software") {
+            return Ok(Some(software.clone()));
+        }
+
+        let software = match properties.get("openslide.vendor").map(String::as_str) {
+            Some("aperio") => properties
+                .get("tiff.ImageDescription")
+                .and_then(|description| description.lines().next())
+                .map(|line| line.trim().to_string()),
+            Some("leica") => properties.get("leica.device-version").cloned(),
+            Some("philips") => properties.get("philips.DICOM_SOFTWARE_VERSIONS").cloned(),
+            _ => None,
+        };
+        Ok(software)
+    }
+
+    /// Get the (mpp_x, mpp_y) microns per pixel from the `openslide.mpp-x` and `openslide.mpp-y`
+    /// properties.
+    ///
+    /// Returns an error if either property is missing or can not be parsed. See `pixel_spacing`
+    /// for a fallback to the TIFF resolution properties.
+    pub fn get_mpp(&self) -> Result<(f64, f64), OpenSlideError> {
+        let properties = self.get_properties()?;
+        let mut mpp = [0.0; 2];
+        for (value, key) in mpp.iter_mut().zip(&["openslide.mpp-x", "openslide.mpp-y"]) {
+            *value = parse_property::<f64>(&properties, key)?
+                .ok_or_else(|| OpenSlideError::MissingProperty(key.to_string()))?;
+        }
+        Ok((mpp[0], mpp[1]))
+    }
+
+    /// Get the orientation of the stored pixels of the slide.
+    ///
+    /// This is taken from the `tiff.Orientation` property when present, and is
+    /// `Orientation::Normal` otherwise, when no property says otherwise. OpenSlide 3.4.1 exposes
+    /// no orientation property for the formats it supports (neither the TIFF tag nor a vendor
+    /// property), so for these slides it is always `Orientation::Normal`.
+    pub fn native_orientation(&self) -> Result<utils::Orientation, OpenSlideError> {
+        let properties = self.get_properties()?;
+        let key = "tiff.Orientation";
+        match parse_property::<u16>(&properties, key)? {
+            Some(value) => utils::Orientation::from_tiff_tag(value).ok_or_else(|| {
+                OpenSlideError::PropertyParse {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                }
+            }),
+            None => Ok(utils::Orientation::Normal),
+        }
+    }
+
+    /// Get the (horizontal, vertical) microns per pixel of every level.
+    ///
+    /// This is the pixel spacing of level 0 from `pixel_spacing()` times the downsample factor of
+    /// each level, or `None` for every level if the slide has no pixel spacing.
+    pub fn mpp_all_levels(&self) -> Result<Vec<Option<(f64, f64)>>, OpenSlideError> {
+        let pixel_spacing = self.pixel_spacing()?;
+        (0..self.get_level_count()?)
+            .map(|level| {
+                let downsample_factor = self.get_level_downsample(level)?;
+                Ok(pixel_spacing.map(|(mpp_x, mpp_y)| {
+                    (mpp_x * downsample_factor, mpp_y * downsample_factor)
+                }))
+            })
+            .collect()
+    }
+
+    /// Get the (x, y, width, height) of the bounding box of the non-empty region of the slide.
+    ///
+    /// This is taken from the `openslide.bounds-x`, `openslide.bounds-y`,
+    /// `openslide.bounds-width` and `openslide.bounds-height` properties, in level 0
+    /// coordinates. Returns `None` if the slide does not have all of them.
+    pub fn get_bounds(&self) -> Result<Option<(u64, u64, u64, u64)>, OpenSlideError> {
+        let properties = self.get_properties()?;
+        let mut bounds = [0; 4];
+        for (value, name) in bounds.iter_mut().zip(&["x", "y", "width", "height"]) {
+            let key = format!("openslide.bounds-{}", name);
+            match parse_property::<u64>(&properties, &key)? {
+                Some(val) => *value = val,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some((bounds[0], bounds[1], bounds[2], bounds[3])))
+    }
+
+    /// Get the objective power (magnification, e.g. 20 or 40) from the `openslide.objective-power`
+    /// property.
+    ///
+    /// A fractional value (e.g. "40.0") is truncated. Returns an error if the property is missing
+    /// or can not be parsed.
+    pub fn get_objective_power(&self) -> Result<u32, OpenSlideError> {
+        let properties = self.get_properties()?;
+        let key = "openslide.objective-power";
+        let power = parse_property::<f64>(&properties, key)?
+            .ok_or_else(|| OpenSlideError::MissingProperty(key.to_string()))?;
+        if !(power >= 0.0 && power <= f64::from(u32::MAX)) {
+            return Err(OpenSlideError::PropertyParse {
+                key: key.to_string(),
+                value: power.to_string(),
+            });
+        }
+        Ok(power as u32)
+    }
+
+    /// Get the pixel spacing (horizontal, vertical) in micrometers (microns) per pixel.
+    ///
+    /// This is taken from the `openslide.mpp-x` and `openslide.mpp-y` properties when present.
+    /// Otherwise, it is computed from the `tiff.XResolution` and `tiff.YResolution` properties,
+    /// which are given in pixels per `tiff.ResolutionUnit` (inch or centimeter). Returns `None`
+    /// if neither is available.
+    pub fn pixel_spacing(&self) -> Result<Option<(f64, f64)>, OpenSlideError> {
+        let properties = self.get_properties()?;
+
+        let mpp_x = parse_property::<f64>(&properties, "openslide.mpp-x")?;
+        let mpp_y = parse_property::<f64>(&properties, "openslide.mpp-y")?;
+        if let (Some(mpp_x), Some(mpp_y)) = (mpp_x, mpp_y) {
+            return Ok(Some((mpp_x, mpp_y)));
+        }
+
+        let microns_per_unit = match properties.get("tiff.ResolutionUnit").map(String::as_str) {
+            Some("inch") => 25_400.0,
+            Some("centimeter") => 10_000.0,
+            _ => return Ok(None),
+        };
+        let x_resolution = parse_property::<f64>(&properties, "tiff.XResolution")?;
+        let y_resolution = parse_property::<f64>(&properties, "tiff.YResolution")?;
+        match (x_resolution, y_resolution) {
+            (Some(x_resolution), Some(y_resolution))
+                if x_resolution > 0.0 && y_resolution > 0.0 =>
+            {
+                Ok(Some((microns_per_unit / x_resolution, microns_per_unit / y_resolution)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Get a fingerprint identifying the slide, e.g. for use as a cache key.
+    ///
+    /// Unlike the filename, the fingerprint does not change when the slide is moved or renamed.
+    /// It relies on the quickhash of the slide contents, which OpenSlide does not compute for
+    /// every format. Without it, the fingerprint falls back to the level 0 dimensions, vendor and
+    /// pixel spacing, so different slides of the same size from the same scanner get the same
+    /// fingerprint.
+    pub fn fingerprint(&self) -> Result<SlideFingerprint, OpenSlideError> {
+        Ok(SlideFingerprint {
+            quickhash_1: self.properties.quickhash_1(),
+            level0_dimensions: self.get_level0_dimensions()?,
+            vendor: self.properties.vendor(),
+            mpp: self.pixel_spacing()?,
+        })
+    }
+
+    /// Get the nominal scan magnification of the slide.
+    ///
+    /// This is based on the objective power (`openslide.objective-power`, or the vendor specific
+    /// `aperio.AppMag`, `hamamatsu.SourceLens` or `leica.objective`). If the slide records none of
+    /// these, the magnification is estimated from the pixel spacing, as 10 divided by the mean
+    /// microns per pixel (i.e. 0.25 microns per pixel is 40x).
+    pub fn scan_resolution_tier(&self) -> Result<ScanTier, OpenSlideError> {
+        let properties = self.get_properties()?;
+        for key in &[
+            "openslide.objective-power",
+            "aperio.AppMag",
+            "hamamatsu.SourceLens",
+            "leica.objective",
+        ] {
+            if let Some(magnification) = parse_property::<f64>(&properties, key)? {
+                return Ok(ScanTier::from_magnification(magnification));
+            }
+        }
+
+        match self.pixel_spacing()? {
+            Some((mpp_x, mpp_y)) if mpp_x > 0.0 && mpp_y > 0.0 => {
+                Ok(ScanTier::from_magnification(20.0 / (mpp_x + mpp_y)))
+            }
+            _ => Err(OpenSlideError::InvalidArgument(String::from(
+                "Slide records neither an objective power nor a pixel spacing"
+            ))),
+        }
     }
-}
 
-impl OpenSlide {
-    /// This method tries to open the slide at the given filename location.
+    /// Get the physical (x, y) position of the top left corner of level 0, in microns.
     ///
-    /// This function can be expensive; avoid calling it unnecessarily. For example, a tile server
-    /// should not create a new object on every tile request. Instead, it should maintain a cache
-    /// of OpenSlide objects and reuse them when possible.
-    pub fn new(filename: &Path) -> Result<OpenSlide, Error> {
-        if !filename.exists() {
-            return Err(format_err!("Error: Nonexisting path: {}", filename.display()));
-        }
+    /// This is the offset of the scanned area on the glass slide recorded by the vendor (Aperio
+    /// `aperio.Left` and `aperio.Top` in millimeters, or Hamamatsu
+    /// `hamamatsu.XOffsetFromSlideCentre` and `hamamatsu.YOffsetFromSlideCentre` in nanometers
+    /// from the slide centre), plus the position of the non-empty region (`openslide.bounds-x`
+    /// and `openslide.bounds-y`) converted with the pixel spacing. Slides scanned on the same
+    /// scanner can then be placed in a common coordinate system. Returns `None` if the slide
+    /// records neither, or records bounds but no pixel spacing.
+    pub fn physical_origin_microns(&self) -> Result<Option<(f64, f64)>, OpenSlideError> {
+        let properties = self.get_properties()?;
 
-        let osr = bindings::open(filename.to_str().ok_or(format_err!("Error: Path to &str"))?)?;
+        let aperio_offset = match (
+            parse_property::<f64>(&properties, "aperio.Left")?,
+            parse_property::<f64>(&properties, "aperio.Top")?,
+        ) {
+            (Some(left), Some(top)) => Some((left * 1000.0, top * 1000.0)),
+            _ => None,
+        };
+        let hamamatsu_offset = match (
+            parse_property::<f64>(&properties, "hamamatsu.XOffsetFromSlideCentre")?,
+            parse_property::<f64>(&properties, "hamamatsu.YOffsetFromSlideCentre")?,
+        ) {
+            (Some(x_offset), Some(y_offset)) => Some((x_offset / 1000.0, y_offset / 1000.0)),
+            _ => None,
+        };
+        let vendor_offset = aperio_offset.or(hamamatsu_offset);
 
-        let mut property_map = HashMap::<String, String>::new();
-        for name in unsafe { bindings::get_property_names(osr)? } {
-            property_map.insert(name.clone(), unsafe { bindings::get_property_value(osr, &name)? });
-        }
-        let properties = properties::Properties::new(&property_map);
+        let bounds_offset = match (
+            parse_property::<f64>(&properties, "openslide.bounds-x")?,
+            parse_property::<f64>(&properties, "openslide.bounds-y")?,
+        ) {
+            (Some(bounds_x), Some(bounds_y)) => match self.pixel_spacing()? {
+                Some((mpp_x, mpp_y)) => Some((bounds_x * mpp_x, bounds_y * mpp_y)),
+                None => return Ok(None),
+            },
+            _ => None,
+        };
 
-        Ok(OpenSlide {
-            osr,
-            properties,
+        Ok(match (vendor_offset, bounds_offset) {
+            (Some((x, y)), Some((bounds_x, bounds_y))) => Some((x + bounds_x, y + bounds_y)),
+            (offset, None) | (None, offset) => offset,
         })
     }
 
-    /// Get the number of levels in the whole slide image.
-    pub fn get_level_count(&self) -> Result<u32, Error> {
-        let num_levels = unsafe { bindings::get_level_count(self.osr)? };
+    /// Check whether the slide has a pixel spacing that can be used for measurements.
+    ///
+    /// This is the case if `pixel_spacing()` finds a spacing, and both its components are finite
+    /// and positive.
+    pub fn is_calibrated(&self) -> Result<bool, OpenSlideError> {
+        Ok(match self.pixel_spacing()? {
+            Some((mpp_x, mpp_y)) => {
+                mpp_x.is_finite() && mpp_y.is_finite() && mpp_x > 0.0 && mpp_y > 0.0
+            }
+            None => false,
+        })
+    }
 
-        if num_levels < -1 {
-            Err(format_err!(
-                "Error: Number of levels is {}, this is an unknown error from OpenSlide. \
-                 OpenSlide returns -1 if an error occured. \
-                 See OpenSlide C API documentation.",
-                num_levels
-            ))
-        } else if num_levels == -1 {
-            Err(format_err!(
-                "Error: Number of levels is -1, this is a known error from OpenSlide. \
-                 OpenSlide returns -1 if an error occured. \
-                 See OpenSlide C API documentation.",
-            ))
-        } else {
-            Ok(num_levels as u32)
+    /// Get the fraction of level 0 that was scanned, i.e. that lies within the slide bounds.
+    ///
+    /// This is the area given by `openslide.bounds-width` and `openslide.bounds-height` divided by
+    /// the area of level 0, and 1 if the slide has no bounds. The rest of level 0 is padding, read
+    /// as transparent pixels.
+    pub fn scanned_fraction(&self) -> Result<f64, OpenSlideError> {
+        let properties = self.get_properties()?;
+        let bounds_width = parse_property::<f64>(&properties, "openslide.bounds-width")?;
+        let bounds_height = parse_property::<f64>(&properties, "openslide.bounds-height")?;
+        let (width, height) = self.get_level0_dimensions()?;
+        match (bounds_width, bounds_height) {
+            (Some(bounds_width), Some(bounds_height)) if width > 0 && height > 0 => {
+                Ok(bounds_width * bounds_height / (width as f64 * height as f64))
+            }
+            _ => Ok(1.0),
         }
     }
 
-    /// Get the dimensions of level 0 (the largest level).
+    /// Get the regions of interest embedded in the slide, or an empty vec if it has none.
     ///
-    /// This method returns the (width, height) number of pixels of the level 0 whole slide image.
+    /// These are the `openslide.region[<index>].*` properties, which OpenSlide sets for the
+    /// scanned regions of some formats. A region with `x` and `y`, and no or a zero `width` and
+    /// `height`, is a point. The label of each annotation is `region[<index>]`.
+    pub fn embedded_annotations(&self) -> Result<Vec<Annotation>, OpenSlideError> {
+        let properties = self.get_properties()?;
+        let mut annotations = Vec::new();
+        for index in 0.. {
+            let label = format!("region[{}]", index);
+            let key = |name: &str| format!("openslide.{}.{}", label, name);
+            let col = parse_property::<u64>(&properties, &key("x"))?;
+            let row = parse_property::<u64>(&properties, &key("y"))?;
+            let (row, col) = match (row, col) {
+                (Some(row), Some(col)) => (row, col),
+                _ => break,
+            };
+            let width = parse_property::<u64>(&properties, &key("width"))?.unwrap_or(0);
+            let height = parse_property::<u64>(&properties, &key("height"))?.unwrap_or(0);
+            let shape = if width == 0 && height == 0 {
+                AnnotationShape::Point((row, col))
+            } else {
+                AnnotationShape::Rectangle((row, col, height, width))
+            };
+            annotations.push(Annotation { label, shape });
+        }
+        Ok(annotations)
+    }
+
+    /// Get the physical (width, height) of level 0 in millimeters.
     ///
-    /// This is the same as calling get_level_dimensions(level) with level=0.
-    pub fn get_level0_dimensions(&self) -> Result<(u64, u64), Error> {
-        let (width, height) = unsafe { bindings::get_level0_dimensions(self.osr)? };
+    /// This is the level 0 dimensions multiplied by the pixel spacing from `pixel_spacing()`.
+    /// Returns `None` if the slide has no pixel spacing.
+    pub fn physical_dimensions_mm(&self) -> Result<Option<(f64, f64)>, OpenSlideError> {
+        let (width, height) = self.get_level0_dimensions()?;
+        let dimensions = self.pixel_spacing()?.map(|(mpp_x, mpp_y)| {
+            (width as f64 * mpp_x / 1000.0, height as f64 * mpp_y / 1000.0)
+        });
+        Ok(dimensions)
+    }
 
-        if width < -1 {
-            return Err(format_err!(
-                "Error: Width is {}, this is an unknown error from OpenSlide. \
-                 OpenSlide returns -1 if an error occured. \
-                 See OpenSlide C API documentation.",
-                width
-            ));
-        } else if width == -1 {
-            return Err(format_err!(
-                "Error: Width is -1, this is a known error from OpenSlide. \
-                 OpenSlide returns -1 if an error occured. \
-                 See OpenSlide C API documentation.",
-            ));
+    /// Plan the extraction of approximately `approx_tile_count` foreground tiles.
+    ///
+    /// The foreground fraction of the slide is estimated from a mask of the smallest level. The
+    /// chosen level is the coarsest level with enough foreground to give at least
+    /// `approx_tile_count` non-overlapping tiles, or level 0 if no level has that. The stride is
+    /// then chosen to give approximately `approx_tile_count` foreground tiles at that level.
+    ///
+    /// No tiles are read, so the plan can be reviewed before the extraction.
+    pub fn plan_tiling(
+        &self,
+        approx_tile_count: usize,
+        tile_size: u32,
+    ) -> Result<TilingPlan, OpenSlideError> {
+        if approx_tile_count == 0 || tile_size == 0 {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Tile count and tile size must be positive, got {} and {}",
+                approx_tile_count, tile_size,
+            )));
         }
 
-        if height < -1 {
-            return Err(format_err!(
-                "Error: Height is {}, this is an unknown error from OpenSlide. \
-                 OpenSlide returns -1 if an error occured. \
-                 See OpenSlide C API documentation.",
-                width
-            ));
-        } else if height == -1 {
-            return Err(format_err!(
-                "Error: Height is -1, this is a known error from OpenSlide. \
-                 OpenSlide returns -1 if an error occured. \
-                 See OpenSlide C API documentation.",
-            ));
+        let num_levels = self.get_level_count()?;
+        if num_levels == 0 {
+            return Err(OpenSlideError::OpenSlideInternal(String::from(
+                "Slide has no levels",
+            )));
         }
+        let smallest_level = num_levels - 1;
+        let (mask_width, mask_height) = self.get_level_dimensions(smallest_level)?;
+        let mask =
+            self.read_region_stored(0, 0, u64::from(smallest_level), mask_height, mask_width)?;
+        let foreground_fraction =
+            utils::foreground_fraction(&mask, FOREGROUND_SATURATION_THRESHOLD);
 
-        Ok((width as u64, height as u64))
+        let tile_area = f64::from(tile_size) * f64::from(tile_size);
+        let mut level = 0;
+        let mut foreground_area = 0.0;
+        for candidate in (0..num_levels).rev() {
+            let (width, height) = self.get_level_dimensions(candidate)?;
+            foreground_area = foreground_fraction * width as f64 * height as f64;
+            if foreground_area / tile_area >= approx_tile_count as f64 || candidate == 0 {
+                level = candidate;
+                break;
+            }
+        }
+
+        let stride = (foreground_area / approx_tile_count as f64).sqrt().floor().max(1.0) as u32;
+        let stride_area = f64::from(stride) * f64::from(stride);
+        let estimated_tile_count = (foreground_area / stride_area) as usize;
+
+        Ok(TilingPlan {
+            level,
+            tile_size,
+            stride,
+            foreground_fraction,
+            estimated_tile_count,
+        })
     }
 
-    /// Get the dimensions of level 0 (the largest level).
+    /// Get the compression type and quality the tiles of a level were stored with, if recorded.
     ///
-    /// This method returns the (width, height) number of pixels of the whole slide image at the
-    /// specified level. Returns an error if the level is invalid
-    pub fn get_level_dimensions<T: Integer + ToPrimitive + Debug + Display + Clone + Copy>(
+    /// Currently, this is only recorded by Aperio slides, and only for level 0, in the first
+    /// section of `tiff.ImageDescription` (e.g. "... (240x240) JPEG/RGB Q=30"). Returns `None`
+    /// for other levels and vendors.
+    pub fn level_compression_info(
         &self,
-        level: T,
-    ) -> Result<(u64, u64), Error> {
+        level: u32,
+    ) -> Result<Option<CompressionInfo>, OpenSlideError> {
         self.assert_level_validity(level)?;
-        let level = level.to_i32().ok_or(format_err!("Conversion to primitive error"))?;
+        if level != 0 || self.properties.vendor().as_deref() != Some("aperio") {
+            return Ok(None);
+        }
 
-        let (width, height) = unsafe { bindings::get_level_dimensions(self.osr, level)?};
+        let description = match self.properties.image_description() {
+            Some(val) => val,
+            None => return Ok(None),
+        };
+        let first_section = description
+            .split(&['|', ';'][..])
+            .next()
+            .unwrap_or("");
 
-        if width < -1 {
-            return Err(format_err!(
-                "Error: Width is {}, this is an unknown error from OpenSlide. \
-                 OpenSlide returns -1 if an error occured. \
-                 See OpenSlide C API documentation.",
-                width
-            ));
-        } else if width == -1 {
-            return Err(format_err!(
-                "Error: Width is -1, this is a known error from openslide. \
-                 OpenSlide returns -1 if an error occured. \
-                 See OpenSlide C API documentation.",
-            ));
+        let mut compression = None;
+        let mut quality = None;
+        for token in first_section.split_whitespace() {
+            if let Some(value) = token.strip_prefix("Q=") {
+                quality = value.parse::<u32>().ok();
+            } else if compression.is_none() && token.contains('/') {
+                compression = token.split('/').next().map(String::from);
+            }
         }
 
-        if height < -1 {
-            return Err(format_err!(
-                "Error: Height is {}, this is an unknown error from OpenSlide. \
-                 OpenSlide returns -1 if an error occured. \
-                 See OpenSlide C API documentation.",
-                width
-            ));
-        } else if height == -1 {
-            return Err(format_err!(
-                "Error: Height is -1, this is a known error from openslide. \
-                 OpenSlide returns -1 if an error occured. \
-                 See OpenSlide C API documentation.",
-            ));
+        Ok(compression.map(|compression| CompressionInfo {
+            compression,
+            quality,
+        }))
+    }
+
+    /// Get a thumbnail of the slide that fits within `max_dim` x `max_dim` pixels.
+    ///
+    /// This is the `thumbnail` associated image if the slide has one, and otherwise a thumbnail
+    /// rendered from the slide itself. Either way, it is shrunk to fit with
+    /// `utils::resize_to_fit()`.
+    pub fn thumbnail_or_render(&self, max_dim: u32) -> Result<RgbaImage, OpenSlideError> {
+        match self.read_associated_image_if_present("thumbnail")? {
+            Some(thumbnail) => Ok(utils::resize_to_fit(&thumbnail, max_dim, max_dim)),
+            None => self.thumbnail(max_dim, max_dim),
         }
+    }
 
-        Ok((width as u64, height as u64))
+    /// Render a thumbnail of the slide that fits within `max_width` x `max_height` pixels.
+    ///
+    /// The whole of the best level for the required downsample (see
+    /// `get_best_level_for_downsample`) is read, and shrunk to fit with `utils::resize_to_fit()`,
+    /// which keeps the aspect ratio. This is also the case when even the smallest level is larger
+    /// than the box, so that level is held in memory as a whole. Unlike `thumbnail_or_render`,
+    /// this never uses the `thumbnail` associated image.
+    pub fn thumbnail(&self, max_width: u32, max_height: u32) -> Result<RgbaImage, OpenSlideError> {
+        if max_width == 0 || max_height == 0 {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Thumbnail (width, height) must be positive, got ({}, {})",
+                max_width, max_height
+            )));
+        }
+
+        let (width, height) = self.get_level0_dimensions()?;
+        let downsample_factor =
+            (width as f64 / f64::from(max_width)).max(height as f64 / f64::from(max_height));
+        let level = self.get_best_level_for_downsample(downsample_factor)?;
+        let (level_width, level_height) = self.get_level_dimensions(level)?;
+        let overview =
+            self.read_region_stored(0, 0, u64::from(level), level_height, level_width)?;
+        Ok(utils::resize_to_fit(&overview, max_width, max_height))
     }
 
-    /// Get the downsampling factor of a given level.
-    pub fn get_level_downsample<
-        T: Integer + ToPrimitive + Debug + Display + Clone + Copy,
-    >(
+    /// Export the slide as a legacy image pyramid, as used by e.g. OpenSeadragon.
+    ///
+    /// The slide is rendered as a whole at every power of two scale (1, 1/2, 1/4, ...) that is at
+    /// least `min_size` pixels along its longest side, composited over the background color, and
+    /// written to `out_dir` as `{level}.jpg`. Level 0 is the smallest image, and the largest level
+    /// is the full resolution. Each image is held in memory as a whole, so this is only suitable
+    /// for slides that fit in memory (and are at most 65535 pixels, the limit of JPEG).
+    pub fn export_legacy_pyramid(
         &self,
-        level: T,
-    ) -> Result<f64, Error> {
-        self.assert_level_validity(level)?;
-        let level = level.to_i32().ok_or(format_err!("Conversion to primitive error"))?;
-        let downsample_factor = unsafe { bindings::get_level_downsample(self.osr, level)? };
+        out_dir: &Path,
+        min_size: u32,
+    ) -> Result<(), OpenSlideError> {
+        if min_size == 0 {
+            return Err(OpenSlideError::InvalidArgument(String::from(
+                "Minimum size must be positive, got 0",
+            )));
+        }
 
-        if downsample_factor < 0.0 {
-            return Err(format_err!(
-                "Error: When trying to get a downsample factor for level {},\
-                 OpenSlide returned a downsample factor {}, this is an error from \
-                 OpenSlide. OpenSlide returns -1.0 if an error occured. \
-                 See OpenSlide C API documentation.",
-                level, downsample_factor
-            ));
+        let (width, height) = self.get_level0_dimensions()?;
+        let mut scales = Vec::new();
+        let mut scale = 1.0;
+        while width.max(height) as f64 / scale >= f64::from(min_size) {
+            scales.push(scale);
+            scale *= 2.0;
         }
 
-        Ok(downsample_factor)
+        fs::create_dir_all(out_dir)?;
+        let background = self.background_color()?;
+        for (level, scale) in scales.iter().rev().enumerate() {
+            let scaled_width = ((width as f64 / scale).round() as u32).max(1);
+            let scaled_height = ((height as f64 / scale).round() as u32).max(1);
+            let image = self.thumbnail(scaled_width, scaled_height)?;
+            let image = DynamicImage::ImageRgb8(utils::composite_over(&image, background));
+            image.save(out_dir.join(format!("{}.jpg", level)))?;
+        }
+        Ok(())
     }
 
-    /// Get the best level to use for displaying the given downsample factor.
-    pub fn get_best_level_for_downsample<
-        T: Num + ToPrimitive + PartialOrd + Debug + Display + Clone + Copy,
-    >(
-        &self,
-        downsample_factor: T,
-    ) -> Result<u32, Error> {
-        if downsample_factor < zero() {
-            return Err(format_err!(
-                "Error: Only non-negative downsample factor is allowed. \
-                 You specified {}. ",
-                downsample_factor
-            ));
+    /// Compute the 256 bin histogram of the grayscale values of a whole level.
+    ///
+    /// The level is read in tiles, so it is never held in memory as a whole. The grayscale values
+    /// are computed like in `read_region_gray`, with transparent pixels getting the gray value of
+    /// the background color. The counts are `u64`, as a level can have more than `u32::MAX`
+    /// pixels.
+    pub fn global_histogram(&self, level: u32) -> Result<[u64; 256], OpenSlideError> {
+        let (width, height) = self.get_level_dimensions(level)?;
+        let downsample_factor = self.get_level_downsample(level)?;
+        let mut histogram = [0u64; 256];
+        for row in (0..height).step_by(HISTOGRAM_TILE_SIZE as usize) {
+            for col in (0..width).step_by(HISTOGRAM_TILE_SIZE as usize) {
+                let tile = self.read_region_gray(
+                    utils::level_to_lvl0_coordinate(row, downsample_factor),
+                    utils::level_to_lvl0_coordinate(col, downsample_factor),
+                    u64::from(level),
+                    HISTOGRAM_TILE_SIZE.min(height - row),
+                    HISTOGRAM_TILE_SIZE.min(width - col),
+                    utils::TransparentGray::Background,
+                )?;
+                for pixel in tile.pixels() {
+                    histogram[pixel.data[0] as usize] += 1;
+                }
+            }
         }
+        Ok(histogram)
+    }
 
-        let level = unsafe {
-            bindings::get_best_level_for_downsample(
-                self.osr,
-                downsample_factor
-                    .to_f64()
-                    .ok_or(format_err!("Conversion to primitive error"))?,
-            )?
+    /// Export a whole level as PNG tiles, optionally with a manifest of the tiles.
+    ///
+    /// The level is covered by tiles of `tile_size` x `tile_size` pixels, where the tiles at the
+    /// bottom and right edges are cut to the level. They are written to `out_dir` as
+    /// `{level}_{row}_{col}.png`, with the (row, column) of the tile in the grid of tiles. With
+    /// `write_manifest`, `out_dir/manifest.csv` lists every tile as
+    /// `filename,level,x_lvl0,y_lvl0,width,height,mpp`, with the position of its top left corner
+    /// in level 0 coordinates, its size in pixels at the level, and the mean microns per pixel at
+    /// the level (empty if the slide is not calibrated). Returns the number of tiles.
+    pub fn export_level(
+        &self,
+        level: u32,
+        tile_size: u32,
+        out_dir: &Path,
+        write_manifest: bool,
+    ) -> Result<usize, OpenSlideError> {
+        if tile_size == 0 {
+            return Err(OpenSlideError::InvalidArgument(String::from(
+                "Tile size must be positive, got 0",
+            )));
+        }
+        let (width, height) = self.get_level_dimensions(level)?;
+        let downsample_factor = self.get_level_downsample(level)?;
+        let mpp = match self.pixel_spacing()? {
+            Some((mpp_x, mpp_y)) => format!("{}", (mpp_x + mpp_y) / 2.0 * downsample_factor),
+            None => String::new(),
         };
 
-        if level < -1 {
-            Err(format_err!(
-                "Error: Returned level is {}, this is an unknown error from OpenSlide. \
-                 OpenSlide returns -1 if an error occured. \
-                 See OpenSlide C API documentation.",
-                level
-            ))
-        } else if level == -1 {
-            Err(format_err!(
-                "Error: Returned level is -1, this is a known error from openslide. \
-                 OpenSlide returns -1 if an error occured. \
-                 See OpenSlide C API documentation.",
-            ))
-        } else {
-            Ok(level as u32)
+        fs::create_dir_all(out_dir)?;
+        let mut manifest = String::from("filename,level,x_lvl0,y_lvl0,width,height,mpp\n");
+        let tile_size = u64::from(tile_size);
+        let mut num_tiles = 0;
+        for (tile_row, row) in (0..height).step_by(tile_size as usize).enumerate() {
+            for (tile_col, col) in (0..width).step_by(tile_size as usize).enumerate() {
+                let x_lvl0 = utils::level_to_lvl0_coordinate(col, downsample_factor);
+                let y_lvl0 = utils::level_to_lvl0_coordinate(row, downsample_factor);
+                let tile_height = tile_size.min(height - row);
+                let tile_width = tile_size.min(width - col);
+                let level = u64::from(level);
+                let tile =
+                    self.read_region_stored(y_lvl0, x_lvl0, level, tile_height, tile_width)?;
+
+                let filename = format!("{}_{}_{}.png", level, tile_row, tile_col);
+                tile.save(out_dir.join(&filename))?;
+                manifest.push_str(&format!(
+                    "{},{},{},{},{},{},{}\n",
+                    filename, level, x_lvl0, y_lvl0, tile_width, tile_height, mpp
+                ));
+                num_tiles += 1;
+            }
+        }
+
+        if write_manifest {
+            fs::write(out_dir.join("manifest.csv"), manifest)?;
         }
+        Ok(num_tiles)
     }
 
-    /// Return (new_height, new_width) where
+    /// Compute a perceptual hash of an overview of the slide.
     ///
-    /// new_height = min(height, max_height)
-    /// new_width = min(width, max_width)
+    /// The overview is the whole smallest level, and the hash is computed with
+    /// `utils::perceptual_hash()`. Unlike `openslide.quickhash-1`, which only matches identical
+    /// slides, two scans of the same slide give hashes with a small `utils::hamming_distance()`.
+    pub fn overview_phash(&self) -> Result<u64, OpenSlideError> {
+        let num_levels = self.get_level_count()?;
+        if num_levels == 0 {
+            return Err(OpenSlideError::OpenSlideInternal(String::from(
+                "Slide has no levels",
+            )));
+        }
+        let smallest_level = num_levels - 1;
+        let (width, height) = self.get_level_dimensions(smallest_level)?;
+        let overview = self.read_region_stored(0, 0, u64::from(smallest_level), height, width)?;
+        Ok(utils::perceptual_hash(&overview))
+    }
+
+    /// The error recorded by OpenSlide, or an error with the `fallback` message if there is none.
+    fn openslide_error(&self, fallback: String) -> OpenSlideError {
+        OpenSlideError::OpenSlideInternal(self.get_error().unwrap_or(fallback))
+    }
+
+    /// Check the buffer read for a region, given by its level, the level 0 (row, column) of its
+    /// top left corner and its (height, width).
     ///
-    /// and max_{height, width} are computed based on the top left corner coordinates and the
-    /// dimensions of the image.
-    fn get_feasible_dimensions<
-        T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy,
-    >(
+    /// A buffer with fewer pixels than the region is a `TruncatedRead` error, and an error
+    /// recorded by OpenSlide during the read is an `OpenSlideInternal` error, both naming the
+    /// region.
+    fn check_read(
         &self,
-        top_left_lvl0_row: T,
-        top_left_lvl0_col: T,
-        level: T,
-        height: T,
-        width: T,
-    ) -> Result<(u64, u64), Error> {
-        let (max_width, max_height) = self.get_level_dimensions(level)?;
-        let downsample_factor = self.get_level_downsample(level)?;
-
-        let tl_row_this_lvl = top_left_lvl0_row
-            .to_f64()
-            .ok_or(format_err!("Conversion to primitive error"))?
-            / downsample_factor;
-        let tl_col_this_lvl = top_left_lvl0_col
-            .to_f64()
-            .ok_or(format_err!("Conversion to primitive error"))?
-            / downsample_factor;
-
-        let new_height = height
-            .to_u64()
-            .ok_or(format_err!("Conversion to primitive error"))?
-            .min(max_height - tl_row_this_lvl.round() as u64);
-        let new_width = width
-            .to_u64()
-            .ok_or(format_err!("Conversion to primitive error"))?
-            .min(max_width - tl_col_this_lvl.round() as u64);
-
-        if new_height < height
-            .to_u64()
-            .ok_or(format_err!("Conversion to primitive error"))?
-        {
-            println!(
-                "WARNING: Requested region height is changed from {} to {} in order to fit",
-                height, new_height
-            );
+        buffer: &[u32],
+        level: u32,
+        top_left_lvl0: (i64, i64),
+        size: (u64, u64),
+    ) -> Result<(), OpenSlideError> {
+        let (row, col) = top_left_lvl0;
+        let (height, width) = size;
+        let num_pixels = height * width;
+        if (buffer.len() as u64) < num_pixels {
+            let mut message = format!("Got {} of {} pixels", buffer.len(), num_pixels);
+            if let Some(msg) = self.get_error() {
+                message = format!("{}: {}", message, msg);
+            }
+            return Err(OpenSlideError::TruncatedRead {
+                level,
+                row,
+                col,
+                height,
+                width,
+                message,
+            });
         }
-        if new_width < width
-            .to_u64()
-            .ok_or(format_err!("conversion to primitive error"))?
-        {
-            println!(
-                "WARNING: Requested region width is changed from {} to {} in order to fit",
-                width, new_width
-            );
+        match self.get_error() {
+            Some(msg) => Err(OpenSlideError::OpenSlideInternal(format!(
+                "OpenSlide error while reading region at level {} with top left (row {}, col {}) \
+                 and (height, width) ({}, {}): {}",
+                level, row, col, height, width, msg
+            ))),
+            None => Ok(()),
         }
+    }
 
-        if new_height > max_height {
-            return Err(format_err!("Requested height {} exceeds maximum {}", height, max_height));
+    /// Check if the given level is valid
+    fn assert_level_validity<T: Integer + ToPrimitive>(
+        &self,
+        level: T,
+    ) -> Result<(), OpenSlideError> {
+        let max_num_levels = self.get_level_count()?;
+        let level = to_u32(level, "level")?;
+        if level >= max_num_levels {
+            return Err(OpenSlideError::LevelOutOfRange {
+                requested: level,
+                max: max_num_levels.saturating_sub(1),
+            });
         }
+        Ok(())
+    }
 
-        if new_width > max_width {
-            return Err(format_err!("Requested width {} exceeds maximum {}", width, max_width));
+    /// Get the macro image with the label area cropped away, leaving the tissue overview.
+    ///
+    /// This uses the known position of the label in the macro image of the vendor. Currently,
+    /// this is only known for Aperio slides, where the label covers the left third of the macro
+    /// image (25 mm at the end of a standard 75 mm x 25 mm glass slide). Returns `None` if the
+    /// slide has no macro image, or if the label position is not known for the vendor.
+    pub fn macro_tissue_only(&self) -> Result<Option<RgbaImage>, OpenSlideError> {
+        if self.properties.vendor().as_deref() != Some("aperio") {
+            return Ok(None);
         }
 
-        Ok((new_height, new_width))
+        let mut macro_image = match self.read_associated_image_if_present("macro")? {
+            Some(image) => image,
+            None => return Ok(None),
+        };
+        let (width, height) = macro_image.dimensions();
+        let label_width = width / 3;
+        let tissue = imageops::crop(&mut macro_image, label_width, 0, width - label_width, height);
+        Ok(Some(tissue.to_image()))
     }
 
-    /// Copy pre-multiplied ARGB data from a whole slide image.
+    /// The background color of the slide, or white if the slide does not record one.
+    fn background_color(&self) -> Result<Rgb<u8>, OpenSlideError> {
+        Ok(Rgb(self.properties.background_color().unwrap_or([255, 255, 255])))
+    }
+
+    /// Read the associated image with the given name.
     ///
-    /// This function reads and decompresses a region of a whole slide image into an RGBA image
+    /// Returns an error if the slide has no associated image with the name. The image is decoded
+    /// on the first call, and cached for later calls.
+    pub fn read_associated_image(&self, name: &str) -> Result<RgbaImage, OpenSlideError> {
+        self.check_associated_image_name(name)?;
+        self.cached_associated_image(name)
+    }
+
+    /// Read the original, compressed bytes of the associated image with the given name, along with
+    /// their format.
     ///
-    /// Args:
-    ///     top_left_lvl0_row: Row coordinate (increasing downwards) of top left pixel position
-    ///     top_left_lvl0_col: Column coordinate (increasing to the right) of top left pixel
-    ///                        position
-    ///     level: At which level to grab the region from
-    ///     height: Height in pixels of the outputted region
-    ///     width: Width in pixels of the outputted region
-    pub fn read_region<T: Integer + Unsigned + ToPrimitive + Debug + Display + Clone + Copy>(
+    /// OpenSlide 3.4.1 only gives the decoded pixels of associated images, so this returns `None`
+    /// for every associated image; `read_associated_image` reads the pixels. Returns an error if
+    /// the slide has no associated image with the name.
+    pub fn read_associated_image_original(
         &self,
-        top_left_lvl0_row: T,
-        top_left_lvl0_col: T,
-        level: T,
-        height: T,
-        width: T,
-    ) -> Result<RgbaImage, Error> {
-        let (height, width) = self.get_feasible_dimensions(
-            top_left_lvl0_row,
-            top_left_lvl0_col,
-            level,
-            height,
-            width,
-        )?;
+        name: &str,
+    ) -> Result<Option<(Vec<u8>, ImageFormat)>, OpenSlideError> {
+        self.check_associated_image_name(name)?;
+        Ok(None)
+    }
 
-        let buffer = unsafe {
-            bindings::read_region(
-                self.osr,
-                top_left_lvl0_col
-                    .to_i64()
-                    .ok_or(format_err!("Conversion to primitive error"))?,
-                top_left_lvl0_row
-                    .to_i64()
-                    .ok_or(format_err!("Conversion to primitive error"))?,
-                level
-                    .to_i32()
-                    .ok_or(format_err!("Conversion to primitive error"))?,
-                width
-                    .to_i64()
-                    .ok_or(format_err!("Conversion to primitive error"))?,
-                height
-                    .to_i64()
-                    .ok_or(format_err!("Conversion to primitive error"))?,
-            )?
-        };
-        let word_repr = utils::WordRepresentation::BigEndian;
-        utils::decode_buffer(&buffer, height, width, word_repr)
+    /// Return an error listing the associated images of the slide if it has none with the name.
+    fn check_associated_image_name(&self, name: &str) -> Result<(), OpenSlideError> {
+        let mut names = unsafe { bindings::get_associated_image_names(self.osr.0)? };
+        if names.iter().any(|associated_name| associated_name == name) {
+            return Ok(());
+        }
+        names.sort();
+        Err(OpenSlideError::InvalidArgument(format!(
+            "Slide has no associated image {}, only {:?}",
+            name, names
+        )))
     }
 
-    /// Get a dictionary of properties associated with the current slide
+    /// Read the associated image with the given name, or `None` if the slide does not have it.
+    fn read_associated_image_if_present(
+        &self,
+        name: &str,
+    ) -> Result<Option<RgbaImage>, OpenSlideError> {
+        let names = unsafe { bindings::get_associated_image_names(self.osr.0)? };
+        if names.iter().any(|associated_name| associated_name == name) {
+            Ok(Some(self.cached_associated_image(name)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Get the decoded associated image with the given name, which the slide must have, decoding
+    /// it on the first call.
     ///
-    /// There are some standard properties to every slide, but also a lot of vendor-specific
-    /// properties. This method returns a HashMap with all key-value pairs of the properties
-    /// associated with the slide.
-    pub fn get_properties(&self) -> Result<HashMap<String, String>, Error> {
-        let mut properties = HashMap::<String, String>::new();
-        for name in unsafe { bindings::get_property_names(self.osr)? } {
-            properties.insert(name.clone(), unsafe { bindings::get_property_value(self.osr, &name)? });
+    /// A failure to decode is not cached, and does not affect the other associated images.
+    fn cached_associated_image(&self, name: &str) -> Result<RgbaImage, OpenSlideError> {
+        if let Some(img) = self.associated_images.borrow().get(name) {
+            return Ok(img.clone());
         }
-        Ok(properties)
+
+        let (width, height) = self.get_associated_image_dimensions(name)?;
+        let buffer = unsafe { bindings::read_associated_image(self.osr.0, name)? };
+        let img = utils::decode_buffer(
+            &buffer,
+            height as u32,
+            width as u32,
+            self.word_representation,
+        )?;
+        self.associated_images
+            .borrow_mut()
+            .insert(name.to_string(), img.clone());
+        Ok(img)
     }
 
+    /// Get the properties that describe the associated image with the given name.
+    ///
+    /// These are the `openslide.associated.<name>.*` properties (e.g. the width and height), and
+    /// for Philips slides, the `philips.PIM_DP_SCANNED_IMAGES[<index>].*` properties of the
+    /// scanned image of the matching type (`LABELIMAGE` or `MACROIMAGE`). The keys are the full
+    /// property names. Returns an error if the slide has no associated image with the name.
+    pub fn associated_image_properties(
+        &self,
+        name: &str,
+    ) -> Result<HashMap<String, String>, OpenSlideError> {
+        self.check_associated_image_name(name)?;
 
-    /// Check if the given level is valid
-    fn assert_level_validity<T: Integer + ToPrimitive>(&self, level: T) -> Result<(), Error> {
-        let max_num_levels = self.get_level_count()?;
-        let level = level.to_u32().ok_or(format_err!("Conversion to primitive error"))?;
-        if level >= max_num_levels {
-            return Err(format_err!(
-                "Error: Specified level {} is larger than the max slide level {}",
-                level, max_num_levels - 1,
-            ));
+        let properties = self.get_properties()?;
+        let mut prefixes = vec![format!("openslide.associated.{}.", name)];
+        let philips_image_type = match name {
+            "label" => Some("LABELIMAGE"),
+            "macro" => Some("MACROIMAGE"),
+            _ => None,
+        };
+        if let Some(image_type) = philips_image_type {
+            for (key, value) in &properties {
+                if key.starts_with("philips.PIM_DP_SCANNED_IMAGES[")
+                    && key.ends_with("].PIM_DP_IMAGE_TYPE")
+                    && value == image_type
+                {
+                    prefixes.push(key.trim_end_matches("PIM_DP_IMAGE_TYPE").to_string());
+                }
+            }
         }
-        Ok(())
+
+        Ok(properties
+            .into_iter()
+            .filter(|(key, _)| prefixes.iter().any(|prefix| key.starts_with(prefix.as_str())))
+            .collect())
     }
 
     /// Get associated images with the current slide
-    pub fn get_associated_images(&self) -> Result<HashMap<String, RgbaImage>, Error> {
-        let mut associated_images = HashMap::<String, RgbaImage>::new();
-        for name in unsafe { bindings::get_associated_image_names(self.osr)? } {
-            let (width, height) = unsafe {
-                bindings::get_associated_image_dimensions(self.osr, &name)?
-            };
-            let word_repr = utils::WordRepresentation::BigEndian;
-            let buffer = unsafe {
-                bindings::read_associated_image(self.osr, &name)?
-            };
-            let img = utils::decode_buffer(&buffer, height as u32, width as u32, word_repr)?;
-            associated_images.insert(name.clone(), img);
-        }
-        Ok(associated_images)
+    ///
+    /// Every image is decoded on its first use, and cached for later calls.
+    pub fn get_associated_images(&self) -> Result<HashMap<String, RgbaImage>, OpenSlideError> {
+        let names = unsafe { bindings::get_associated_image_names(self.osr.0)? };
+        names
+            .into_iter()
+            .map(|name| {
+                let img = self.cached_associated_image(&name)?;
+                Ok((name, img))
+            })
+            .collect()
+    }
+}
+
+/// Convert a generic argument to a `i32`, with an error naming the argument (`what`).
+fn to_i32<T: ToPrimitive>(value: T, what: &str) -> Result<i32, OpenSlideError> {
+    value.to_i32().ok_or_else(|| {
+        OpenSlideError::InvalidArgument(format!("Unable to convert argument `{}` to i32", what))
+    })
+}
+
+/// Convert a generic argument to a `i64`, with an error naming the argument (`what`).
+fn to_i64<T: ToPrimitive>(value: T, what: &str) -> Result<i64, OpenSlideError> {
+    value.to_i64().ok_or_else(|| {
+        OpenSlideError::InvalidArgument(format!("Unable to convert argument `{}` to i64", what))
+    })
+}
+
+/// Convert a generic argument to a `u32`, with an error naming the argument (`what`).
+fn to_u32<T: ToPrimitive>(value: T, what: &str) -> Result<u32, OpenSlideError> {
+    value.to_u32().ok_or_else(|| {
+        OpenSlideError::InvalidArgument(format!("Unable to convert argument `{}` to u32", what))
+    })
+}
+
+/// Convert a generic argument to a `u64`, with an error naming the argument (`what`).
+fn to_u64<T: ToPrimitive>(value: T, what: &str) -> Result<u64, OpenSlideError> {
+    value.to_u64().ok_or_else(|| {
+        OpenSlideError::InvalidArgument(format!("Unable to convert argument `{}` to u64", what))
+    })
+}
+
+/// Convert a generic argument to a `f64`, with an error naming the argument (`what`).
+fn to_f64<T: ToPrimitive>(value: T, what: &str) -> Result<f64, OpenSlideError> {
+    value.to_f64().ok_or_else(|| {
+        OpenSlideError::InvalidArgument(format!("Unable to convert argument `{}` to f64", what))
+    })
+}
+
+/// Parse the value of a property, or return `None` if the slide does not have the property.
+fn parse_property<T: FromStr>(
+    properties: &HashMap<String, String>,
+    key: &str,
+) -> Result<Option<T>, OpenSlideError> {
+    match properties.get(key) {
+        Some(value) => match value.parse::<T>() {
+            Ok(val) => Ok(Some(val)),
+            Err(_) => Err(OpenSlideError::PropertyParse {
+                key: key.to_string(),
+                value: value.to_string(),
+            }),
+        },
+        None => Ok(None),
     }
 }
+
+/// Lazily open every slide in a directory.
+///
+/// The files in the directory (not in its subdirectories) are checked with
+/// `bindings::detect_vendor()`, and the recognized ones are opened one by one as the iterator
+/// advances. Files that are not recognized by OpenSlide are skipped silently. Failures are yielded
+/// as errors, together with the path they concern: a file name that is not valid UTF-8 (as
+/// `InvalidPath`), a recognized slide that can not be opened, or the directory itself when it can
+/// not be read.
+pub fn open_dir(
+    path: &Path,
+) -> impl Iterator<Item = Result<(PathBuf, OpenSlide), (PathBuf, OpenSlideError)>> {
+    let dir = path.to_path_buf();
+    let entries: Box<dyn Iterator<Item = Result<PathBuf, (PathBuf, OpenSlideError)>>> =
+        match fs::read_dir(path) {
+            Ok(read_dir) => Box::new(read_dir.map(move |entry| match entry {
+                Ok(entry) => Ok(entry.path()),
+                Err(err) => Err((dir.clone(), OpenSlideError::Io(err))),
+            })),
+            Err(err) => Box::new(iter::once(Err((dir, OpenSlideError::Io(err))))),
+        };
+
+    entries.filter_map(|entry| {
+        let path = match entry {
+            Ok(path) => path,
+            Err(err) => return Some(Err(err)),
+        };
+        if !path.is_file() {
+            return None;
+        }
+        let filename = match path.to_str() {
+            Some(filename) => filename,
+            None => return Some(Err((path.clone(), OpenSlideError::InvalidPath(path)))),
+        };
+        if bindings::detect_vendor(filename).is_err() {
+            return None;
+        }
+        match OpenSlide::new(&path) {
+            Ok(slide) => Some(Ok((path, slide))),
+            Err(err) => Some(Err((path, err))),
+        }
+    })
+}
@@ -0,0 +1,170 @@
+//! The error type of this crate
+//!
+
+use std::error::Error;
+use std::path::PathBuf;
+use std::{ffi, fmt, io};
+
+#[cfg(feature = "image")]
+use image::ImageError;
+
+/// An error from OpenSlide or from this crate
+///
+/// Every fallible function in this crate returns this error. It implements `std::error::Error`,
+/// and is `Send`, `Sync` and `'static`, so it converts into the boxed error types of other crates
+/// with `?`. This includes `anyhow::Error`, by the blanket `From` implementation of anyhow, so
+/// there is no `anyhow` feature.
+#[derive(Debug)]
+pub enum OpenSlideError {
+    /// The path of a slide does not exist
+    NonexistentPath(PathBuf),
+    /// The path of a slide is not valid UTF-8, so it can not be given to OpenSlide
+    InvalidPath(PathBuf),
+    /// OpenSlide does not recognize, or could not open, a slide
+    OpenFailed(String),
+    /// A level beyond the levels of the slide was requested
+    LevelOutOfRange {
+        /// The requested level
+        requested: u32,
+        /// The highest level of the slide
+        max: u32,
+    },
+    /// A property that is needed is missing from the slide, e.g. `openslide.mpp-x`
+    MissingProperty(String),
+    /// A property has a value that could not be parsed
+    PropertyParse {
+        /// Name of the property, e.g. `openslide.mpp-x`
+        key: String,
+        /// The value that could not be parsed
+        value: String,
+    },
+    /// OpenSlide reported an error, e.g. from `openslide_get_error()` or a -1 return value
+    OpenSlideInternal(String),
+    /// An argument is invalid, e.g. a region that extends beyond the slide or a zero tile size
+    InvalidArgument(String),
+    /// A read returned fewer pixels than the region it was for
+    TruncatedRead {
+        /// Level of the region
+        level: u32,
+        /// Row of the top left corner of the region, in level 0 coordinates
+        row: i64,
+        /// Column of the top left corner of the region, in level 0 coordinates
+        col: i64,
+        /// Height of the region at `level`
+        height: u64,
+        /// Width of the region at `level`
+        width: u64,
+        /// The number of pixels read, and the message from `openslide_get_error()` if any
+        message: String,
+    },
+    /// A read was cancelled before it finished
+    Cancelled(String),
+    /// Reading or writing a file failed
+    Io(io::Error),
+    /// Decoding or encoding an image failed
+    #[cfg(feature = "image")]
+    Image(ImageError),
+}
+
+impl fmt::Display for OpenSlideError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OpenSlideError::NonexistentPath(ref path) => {
+                write!(f, "Error: Nonexisting path: {}", path.display())
+            }
+            OpenSlideError::InvalidPath(ref path) => {
+                write!(f, "Error: Path is not valid UTF-8: {}", path.display())
+            }
+            OpenSlideError::LevelOutOfRange { requested, max } => write!(
+                f,
+                "Error: Specified level {} is larger than the max slide level {}",
+                requested, max
+            ),
+            OpenSlideError::MissingProperty(ref key) => {
+                write!(f, "Error: Slide has no property {}", key)
+            }
+            OpenSlideError::PropertyParse { ref key, ref value } => {
+                write!(
+                    f,
+                    "Error: Unable to parse property {} with value {}",
+                    key, value
+                )
+            }
+            OpenSlideError::TruncatedRead {
+                level,
+                row,
+                col,
+                height,
+                width,
+                ref message,
+            } => write!(
+                f,
+                "Error: Truncated read of region at level {} with top left (row {}, col {}) \
+                 and (height, width) ({}, {}): {}",
+                level, row, col, height, width, message
+            ),
+            OpenSlideError::OpenFailed(ref msg)
+            | OpenSlideError::OpenSlideInternal(ref msg)
+            | OpenSlideError::InvalidArgument(ref msg)
+            | OpenSlideError::Cancelled(ref msg) => write!(f, "Error: {}", msg),
+            OpenSlideError::Io(ref err) => write!(f, "Error: {}", err),
+            #[cfg(feature = "image")]
+            OpenSlideError::Image(ref err) => write!(f, "Error: {}", err),
+        }
+    }
+}
+
+impl Error for OpenSlideError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            OpenSlideError::Io(ref err) => Some(err),
+            #[cfg(feature = "image")]
+            OpenSlideError::Image(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for OpenSlideError {
+    fn from(err: io::Error) -> Self {
+        OpenSlideError::Io(err)
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<ImageError> for OpenSlideError {
+    fn from(err: ImageError) -> Self {
+        OpenSlideError::Image(err)
+    }
+}
+
+/// Strings given to OpenSlide (paths and property or image names) can not contain nul bytes.
+impl From<ffi::NulError> for OpenSlideError {
+    fn from(err: ffi::NulError) -> Self {
+        OpenSlideError::InvalidArgument(format!(
+            "String given to OpenSlide has a nul byte at position {}",
+            err.nul_position()
+        ))
+    }
+}
+
+/// For use in functions returning `std::io::Result`. The kind of the `std::io::Error` follows the
+/// variant, e.g. `NotFound` for `NonexistentPath`.
+impl From<OpenSlideError> for io::Error {
+    fn from(err: OpenSlideError) -> Self {
+        let kind = match err {
+            OpenSlideError::Io(err) => return err,
+            OpenSlideError::NonexistentPath(_) => io::ErrorKind::NotFound,
+            OpenSlideError::InvalidPath(_)
+            | OpenSlideError::LevelOutOfRange { .. }
+            | OpenSlideError::InvalidArgument(_) => io::ErrorKind::InvalidInput,
+            OpenSlideError::OpenFailed(_)
+            | OpenSlideError::MissingProperty(_)
+            | OpenSlideError::PropertyParse { .. } => io::ErrorKind::InvalidData,
+            OpenSlideError::TruncatedRead { .. } => io::ErrorKind::UnexpectedEof,
+            OpenSlideError::Cancelled(_) => io::ErrorKind::Interrupted,
+            _ => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, err)
+    }
+}
@@ -8,7 +8,7 @@
 //!
 //! ```text,no_run
 //! OpenSlide 3.4.1
-//! Rust 1.26
+//! Rust 1.73
 //! ```
 //!
 //! I cannot guarantee that it works for other versions.
@@ -64,13 +64,12 @@
 //! ```rust,no_run
 //! //! Example using the raw binding api
 //!
-//! extern crate failure;
 //! extern crate openslide;
 //!
-//! use failure::Error;
+//! use openslide::OpenSlideError;
 //! use openslide::bindings;
 //!
-//! fn main() -> Result<(), Error> {
+//! fn main() -> Result<(), OpenSlideError> {
 //!     let filename = "assets/CMU-1-Small-Region.svs";
 //!     let osr = bindings::open(filename)?;
 //!     let num_levels = bindings::get_level_count(osr)?;
@@ -94,16 +93,13 @@
 //! ```rust,no_run
 //! //! Example using the convenience binding api
 //!
-//! extern crate failure;
 //! extern crate openslide;
 //!
-//! use std::path::Path;
-//! use failure::Error;
+//! use openslide::OpenSlideError;
 //! use openslide::OpenSlide;
 //!
-//! fn main() -> Result<(), Error> {
-//!     let filename = Path::new("assets/CMU-1-Small-Region.svs");
-//!     let os = openslide::OpenSlide::new(&filename)?;
+//! fn main() -> Result<(), OpenSlideError> {
+//!     let os = openslide::OpenSlide::new("assets/CMU-1-Small-Region.svs")?;
 //!     let num_levels = os.get_level_count()?;
 //!     println!("Slide has {} levels", num_levels);
 //!
@@ -113,10 +109,12 @@
 //!
 
 extern crate byteorder;
-extern crate failure;
+#[cfg(feature = "image")]
 extern crate image;
 extern crate libc;
 extern crate num;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 
 /*
 pub use bindings::{OpenSlideT,
@@ -135,10 +133,33 @@ pub use bindings::{OpenSlideT,
 };
 */
 
-pub use convenience::{OpenSlide,
+#[cfg(feature = "image")]
+pub use convenience::{open_dir,
+                      Annotation,
+                      AnnotationShape,
+                      CacheCapacity,
+                      CachedOpenSlide,
+                      CacheStats,
+                      CompressionInfo,
+                      DeepZoomGenerator,
+                      OpenSlide,
+                      OpenSlideBuilder,
+                      ReadRegion,
+                      Region,
+                      ScanTier,
+                      SlideFingerprint,
+                      SlideReader,
+                      SyncOpenSlide,
+                      TileIterator,
+                      TileReadEvent,
+                      TilingPlan,
 };
+pub use error::OpenSlideError;
+pub use utils::supported_extensions;
 
 pub mod bindings;
 pub mod utils;
+#[cfg(feature = "image")]
 mod convenience;
+mod error;
 pub mod properties;
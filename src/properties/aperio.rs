@@ -1,9 +1,12 @@
 //! Aperio properties
 //!
 
-use num::Num;
-use std::{f32, u32};
+use error::OpenSlideError;
 
+use super::parse_value;
+
+/// Vendor specific properties that are available under the name `aperio.<property>` in the
+/// HashMap returned from the `OpenSlide::get_properties()` method.
 #[derive(Clone, Debug, Default)]
 pub struct Aperio {
     pub filename: Option<String>,
@@ -36,7 +39,8 @@ pub struct Aperio {
 }
 
 impl Aperio {
-    pub fn parse_property_name(&mut self, name: &str, value: &str) {
+    /// Parse a property, returning an error if a numeric value is malformed.
+    pub fn parse_property_name(&mut self, name: &str, value: &str) -> Result<(), OpenSlideError> {
         match name {
             "aperio.Filename" => self.filename = Some(String::from(value)),
             "aperio.Title" => self.title = Some(String::from(value)),
@@ -49,45 +53,26 @@ impl Aperio {
             "aperio.User" => self.user = Some(String::from(value)),
             "aperio.ICC Profile" => self.icc_profile = Some(String::from(value)),
             "aperio.Parmset" => self.parmset = Some(String::from(value)),
-            "aperio.OriginalHeight" => {
-                self.original_height = Some(u32::from_str_radix(value, 10).unwrap())
-            }
-            "aperio.OriginalWidth" => {
-                self.original_width = Some(u32::from_str_radix(value, 10).unwrap())
-            }
-            "aperio.Top" => self.top = Some(f32::from_str_radix(value, 10).unwrap()),
-            "aperio.Left" => self.left = Some(f32::from_str_radix(value, 10).unwrap()),
-            "aperio.MPP" => self.mpp = Some(f32::from_str_radix(value, 10).unwrap()),
-            "aperio.LineCameraSkew" => {
-                self.line_camera_skew = Some(f32::from_str_radix(value, 10).unwrap())
-            }
-            "aperio.LineAreaXOffset" => {
-                self.line_area_x_offset = Some(f32::from_str_radix(value, 10).unwrap())
-            }
-            "aperio.LineAreaYOffset" => {
-                self.line_area_y_offset = Some(f32::from_str_radix(value, 10).unwrap())
-            }
-            "aperio.Focus Offset" => {
-                self.focus_offset = Some(f32::from_str_radix(value, 10).unwrap())
-            }
-            "aperio.AppMag" => self.app_mag = Some(u32::from_str_radix(value, 10).unwrap()),
-            "aperio.StripeWidth" => {
-                self.stripe_width = Some(u32::from_str_radix(value, 10).unwrap())
-            }
-            "aperio.Filtered" => self.filtered = Some(u32::from_str_radix(value, 10).unwrap()),
-            "aperio.DisplayColor" => {
-                self.display_color = Some(u32::from_str_radix(value, 10).unwrap())
-            }
-            "aperio.Exposure Time" => {
-                self.exposure_time = Some(u32::from_str_radix(value, 10).unwrap())
-            }
-            "aperio.Exposure Scale" => {
-                self.exposure_scale = Some(f32::from_str_radix(value, 10).unwrap())
-            }
+            "aperio.OriginalHeight" => self.original_height = Some(parse_value(name, value)?),
+            "aperio.OriginalWidth" => self.original_width = Some(parse_value(name, value)?),
+            "aperio.Top" => self.top = Some(parse_value(name, value)?),
+            "aperio.Left" => self.left = Some(parse_value(name, value)?),
+            "aperio.MPP" => self.mpp = Some(parse_value(name, value)?),
+            "aperio.LineCameraSkew" => self.line_camera_skew = Some(parse_value(name, value)?),
+            "aperio.LineAreaXOffset" => self.line_area_x_offset = Some(parse_value(name, value)?),
+            "aperio.LineAreaYOffset" => self.line_area_y_offset = Some(parse_value(name, value)?),
+            "aperio.Focus Offset" => self.focus_offset = Some(parse_value(name, value)?),
+            "aperio.AppMag" => self.app_mag = Some(parse_value(name, value)?),
+            "aperio.StripeWidth" => self.stripe_width = Some(parse_value(name, value)?),
+            "aperio.Filtered" => self.filtered = Some(parse_value(name, value)?),
+            "aperio.DisplayColor" => self.display_color = Some(parse_value(name, value)?),
+            "aperio.Exposure Time" => self.exposure_time = Some(parse_value(name, value)?),
+            "aperio.Exposure Scale" => self.exposure_scale = Some(parse_value(name, value)?),
             "aperio.SessonMode" => self.sesson_mode = Some(String::from(value)),
             //_ => println!("Could not parse property name {} and value {}", name, value),
             _ => {},
         }
+        Ok(())
     }
 
     /// Print available properties (key, value) (where the value is not `None`).
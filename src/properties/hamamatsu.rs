@@ -1,112 +1,104 @@
 //! Hamamatsu properties
 //!
 
-#[derive(Clone, Debug)]
-struct Slant {
-    left_top: Option<(u64, u64, u64)>,
-    left_bottom: Option<(u64, u64, u64)>,
-    right_top: Option<(u64, u64, u64)>,
-    right_bottom: Option<(u64, u64, u64)>,
-}
-
-#[derive(Clone, Debug)]
-struct FocalPlane {
-    left_top: Option<(u64, u64, u64)>,
-    left_bottom: Option<(u64, u64, u64)>,
-    right_top: Option<(u64, u64, u64)>,
-    right_bottom: Option<(u64, u64, u64)>,
-}
-
-#[derive(Clone, Debug)]
-struct Ahex {
-    value: Option<String>,        // [u8; 256] ?
-    ploidy: Option<String>,       // [u8; 256] ?
-    fluorescence: Option<String>, // [u8; 256] ?
-}
+use error::OpenSlideError;
 
-#[derive(Clone, Debug)]
-struct Exposure {
-    barcode_macro: Option<u32>,
-    slide_darkfield_macro: Option<u32>,
-    slide_macro: Option<u32>,
-}
-
-#[derive(Clone, Debug)]
-struct Roi {
-    barcode_macro: Option<(u32, u32, u32, u32)>,
-    slide_macro: Option<(u32, u32, u32, u32)>,
-}
-
-#[derive(Clone, Debug)]
-struct Valid {
-    dltp: Option<u32>,
-    ddkp: Option<u32>,
-    dshp: Option<u32>,
-}
+use super::parse_value;
 
-#[derive(Clone, Debug)]
-struct Pshv {
-    value: Option<u32>,
-    magn_10x: Option<u32>,
-    magn_40x: Option<u32>,
-    ploidy: Option<u32>,
-    ploidy_10x: Option<u32>,
-    ploidy_40x: Option<u32>,
-}
-
-#[derive(Clone, Debug)]
-struct ZCoarse {
-    value: Option<(u32, u32, u32, u32)>,
-}
-
-#[derive(Clone, Debug)]
-struct ZFine {
-    value: Option<(u32, u32, u32, u32)>,
-}
-
-#[derive(Clone, Debug)]
-struct Yrnp {
-    value: Option<(u32, u32, u32, u32)>,
+/// Vendor specific properties that are available under the name `hamamatsu.<property>` in the
+/// HashMap returned from the `OpenSlide::get_properties()` method.
+#[derive(Clone, Debug, Default)]
+pub struct Hamamatsu {
+    pub source_lens: Option<f32>,
+    pub x_offset_from_slide_centre: Option<i64>,
+    pub y_offset_from_slide_centre: Option<i64>,
+    pub objective: Option<String>,
+    pub no_layers: Option<u32>,
+    pub layer_spacing: Option<i64>,
+    pub macro_image: Option<String>,
+    pub image_file: Option<String>,
+    pub map_file: Option<String>,
+    pub optimisation_file: Option<String>,
+    pub no_jpeg_columns: Option<u32>,
+    pub no_jpeg_rows: Option<u32>,
+    pub physical_width: Option<i64>,
+    pub physical_height: Option<i64>,
+    pub reference: Option<String>,
 }
 
-#[derive(Clone, Debug)]
-struct Ccd {
-    width: Option<u32>,
-    width_ploidy: Option<u32>,
-    height: Option<u32>,
-}
+impl Hamamatsu {
+    pub fn parse_property_name(&mut self, name: &str, value: &str) -> Result<(), OpenSlideError> {
+        match name {
+            "hamamatsu.SourceLens" => self.source_lens = Some(parse_value(name, value)?),
+            "hamamatsu.XOffsetFromSlideCentre" => {
+                self.x_offset_from_slide_centre = Some(parse_value(name, value)?)
+            }
+            "hamamatsu.YOffsetFromSlideCentre" => {
+                self.y_offset_from_slide_centre = Some(parse_value(name, value)?)
+            }
+            "hamamatsu.Objective" => self.objective = Some(String::from(value)),
+            "hamamatsu.NoLayers" => self.no_layers = Some(parse_value(name, value)?),
+            "hamamatsu.LayerSpacing" => self.layer_spacing = Some(parse_value(name, value)?),
+            "hamamatsu.MacroImage" => self.macro_image = Some(String::from(value)),
+            "hamamatsu.ImageFile" => self.image_file = Some(String::from(value)),
+            "hamamatsu.MapFile" => self.map_file = Some(String::from(value)),
+            "hamamatsu.OptimisationFile" => self.optimisation_file = Some(String::from(value)),
+            "hamamatsu.NoJpegColumns" => self.no_jpeg_columns = Some(parse_value(name, value)?),
+            "hamamatsu.NoJpegRows" => self.no_jpeg_rows = Some(parse_value(name, value)?),
+            "hamamatsu.PhysicalWidth" => self.physical_width = Some(parse_value(name, value)?),
+            "hamamatsu.PhysicalHeight" => self.physical_height = Some(parse_value(name, value)?),
+            "hamamatsu.Reference" => self.reference = Some(String::from(value)),
+            _ => {}
+        }
+        Ok(())
+    }
 
-#[derive(Clone, Debug)]
-pub struct Hamamatsu {
-    product: Option<String>,
-    system_version: Option<String>,
-    updated: Option<String>, // Date
-    created: Option<String>, // Date
-    color_filter_id: Option<String>,
-    reference: Option<String>,
-    calibration_version: Option<u32>,
-    fine_focus_pitch: Option<u32>,
-    coarse_focus_pitch: Option<u32>,
-    stage_center: Option<(u32, u32)>,
-    slide_thickness: Option<u32>,
-    lane_shift_amount: Option<i32>,
-    variable_exposure_time: Option<u32>,
-    cube_kind: Option<u32>,
-    target_white_intensity: Option<u32>,
-    source_lens: Option<u32>,
-    objective_lens_magnificant: Option<f32>,
-    x_offset_from_slide_centre: Option<i64>,
-    y_offset_from_slide_centre: Option<i64>,
-    macro_s_n: Option<String>,
-    ndp_s_n: Option<String>,
-    slant: Option<Slant>,
-    exposure: Option<Exposure>,
-    ahex: Option<Vec<Ahex>>,
-    valid: Option<Valid>,
-    pshv: Option<Pshv>,
-    roi: Option<Roi>,
-    z_coarse: Option<Vec<ZCoarse>>,
-    z_fine: Option<Vec<ZFine>>,
-    focal_plane: Option<FocalPlane>,
-    yrnp: Option<Vec<Yrnp>>,
+    /// Print available properties (key, value) (where the value is not `None`).
+    pub fn print_available(&self) {
+        if let Some(ref val) = self.source_lens {
+            println!("Source lens: {}", val)
+        }
+        if let Some(ref val) = self.x_offset_from_slide_centre {
+            println!("X offset from slide centre: {}", val)
+        }
+        if let Some(ref val) = self.y_offset_from_slide_centre {
+            println!("Y offset from slide centre: {}", val)
+        }
+        if let Some(ref val) = self.objective {
+            println!("Objective: {}", val)
+        }
+        if let Some(ref val) = self.no_layers {
+            println!("Number of layers: {}", val)
+        }
+        if let Some(ref val) = self.layer_spacing {
+            println!("Layer spacing: {}", val)
+        }
+        if let Some(ref val) = self.macro_image {
+            println!("Macro image: {}", val)
+        }
+        if let Some(ref val) = self.image_file {
+            println!("Image file: {}", val)
+        }
+        if let Some(ref val) = self.map_file {
+            println!("Map file: {}", val)
+        }
+        if let Some(ref val) = self.optimisation_file {
+            println!("Optimisation file: {}", val)
+        }
+        if let Some(ref val) = self.no_jpeg_columns {
+            println!("Number of JPEG columns: {}", val)
+        }
+        if let Some(ref val) = self.no_jpeg_rows {
+            println!("Number of JPEG rows: {}", val)
+        }
+        if let Some(ref val) = self.physical_width {
+            println!("Physical width: {}", val)
+        }
+        if let Some(ref val) = self.physical_height {
+            println!("Physical height: {}", val)
+        }
+        if let Some(ref val) = self.reference {
+            println!("Reference: {}", val)
+        }
+    }
 }
@@ -0,0 +1,60 @@
+//! Leica properties
+//!
+
+use error::OpenSlideError;
+
+use super::parse_value;
+
+/// Vendor specific properties that are available under the name `leica.<property>` in the
+/// HashMap returned from the `OpenSlide::get_properties()` method.
+#[derive(Clone, Debug, Default)]
+pub struct Leica {
+    pub aperture: Option<f32>,
+    pub barcode: Option<String>,
+    pub creation_date: Option<String>,
+    pub device_model: Option<String>,
+    pub device_version: Option<String>,
+    pub illumination_source: Option<String>,
+    pub objective: Option<f32>,
+}
+
+impl Leica {
+    pub fn parse_property_name(&mut self, name: &str, value: &str) -> Result<(), OpenSlideError> {
+        match name {
+            "leica.aperture" => self.aperture = Some(parse_value(name, value)?),
+            "leica.barcode" => self.barcode = Some(String::from(value)),
+            "leica.creation-date" => self.creation_date = Some(String::from(value)),
+            "leica.device-model" => self.device_model = Some(String::from(value)),
+            "leica.device-version" => self.device_version = Some(String::from(value)),
+            "leica.illumination-source" => self.illumination_source = Some(String::from(value)),
+            "leica.objective" => self.objective = Some(parse_value(name, value)?),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Print available properties (key, value) (where the value is not `None`).
+    pub fn print_available(&self) {
+        if let Some(ref val) = self.aperture {
+            println!("Aperture: {}", val)
+        }
+        if let Some(ref val) = self.barcode {
+            println!("Barcode: {}", val)
+        }
+        if let Some(ref val) = self.creation_date {
+            println!("Creation date: {}", val)
+        }
+        if let Some(ref val) = self.device_model {
+            println!("Device model: {}", val)
+        }
+        if let Some(ref val) = self.device_version {
+            println!("Device version: {}", val)
+        }
+        if let Some(ref val) = self.illumination_source {
+            println!("Illumination source: {}", val)
+        }
+        if let Some(ref val) = self.objective {
+            println!("Objective: {}", val)
+        }
+    }
+}
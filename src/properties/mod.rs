@@ -2,13 +2,22 @@
 //!
 
 mod aperio;
-mod openslide;
+mod hamamatsu;
+mod leica;
+mod openslide_std;
+mod philips;
 mod tiff;
-//mod hamamatsu;
 
 use std::collections::HashMap;
+use std::str::FromStr;
 
-use self::openslide::LevelProperties;
+use error::OpenSlideError;
+
+pub use self::aperio::Aperio;
+pub use self::hamamatsu::Hamamatsu;
+pub use self::leica::Leica;
+pub use self::openslide_std::LevelProperties;
+pub use self::philips::Philips;
 
 /// This struct defines an inferface to the various properties of the various formats.
 ///
@@ -34,9 +43,12 @@ use self::openslide::LevelProperties;
 ///
 #[derive(Clone, Debug)]
 pub struct Properties {
-    openslide_properties: openslide::OpenSlide,
+    openslide_properties: openslide_std::Standard,
     tiff_properties: tiff::Tiff,
-    aperio_properties: aperio::Aperio,
+    aperio_properties: Option<Aperio>,
+    hamamatsu_properties: Option<Hamamatsu>,
+    leica_properties: Option<Leica>,
+    philips_properties: Option<Philips>,
 }
 
 impl Properties {
@@ -45,29 +57,68 @@ impl Properties {
     /// This is done by submitting a property_map, which is obtained from the
     /// `OpenSlide::get_properties()` method, but this is abstracted away from the user, and
     /// happens automatically when defining an `OpenSlide` struct.
-    pub fn new(property_map: &HashMap<String, String>) -> Self {
+    ///
+    /// Returns an error if a known numeric Aperio property has a malformed value.
+    pub fn new(property_map: &HashMap<String, String>) -> Result<Self, OpenSlideError> {
         let mut tiff_properties = tiff::Tiff::default();
         // Openslide properties requires special treatement because we need to find out how many
         // levels there are in the initialization.
-        let mut openslide_properties = openslide::OpenSlide::new(property_map);
-        let mut aperio_properties = aperio::Aperio::default();
+        let mut openslide_properties = openslide_std::Standard::new(property_map);
+        let mut aperio_properties: Option<Aperio> = None;
+        let mut hamamatsu_properties: Option<Hamamatsu> = None;
+        let mut leica_properties: Option<Leica> = None;
+        let mut philips_properties: Option<Philips> = None;
 
         for (key, value) in property_map {
             let parent = key.split('.').nth(0);
             match parent {
-                Some("openslide") => openslide_properties.parse_property_name(key, value),
+                Some("openslide") => openslide_properties.parse_property_name(key, value)?,
                 Some("tiff") => tiff_properties.parse_property_name(key, value),
-                Some("aperio") => aperio_properties.parse_property_name(key, value),
+                Some("aperio") => aperio_properties
+                    .get_or_insert_with(Aperio::default)
+                    .parse_property_name(key, value)?,
+                Some("hamamatsu") => hamamatsu_properties
+                    .get_or_insert_with(Hamamatsu::default)
+                    .parse_property_name(key, value)?,
+                Some("leica") => leica_properties
+                    .get_or_insert_with(Leica::default)
+                    .parse_property_name(key, value)?,
+                Some("philips") => philips_properties
+                    .get_or_insert_with(Philips::default)
+                    .parse_property_name(key, value)?,
                 //_ => println!("Could not parse {}", key),
                 _ => {},
             }
         }
 
-        Properties {
+        Ok(Properties {
             tiff_properties,
             openslide_properties,
             aperio_properties,
-        }
+            hamamatsu_properties,
+            leica_properties,
+            philips_properties,
+        })
+    }
+
+    /// Aperio properties (`aperio.<property>`), or `None` if the slide has none.
+    pub fn aperio(&self) -> Option<&Aperio> {
+        self.aperio_properties.as_ref()
+    }
+
+    /// Hamamatsu properties (`hamamatsu.<property>`), or `None` if the slide has none.
+    pub fn hamamatsu(&self) -> Option<&Hamamatsu> {
+        self.hamamatsu_properties.as_ref()
+    }
+
+    /// Leica properties (`leica.<property>`), or `None` if the slide has none.
+    pub fn leica(&self) -> Option<&Leica> {
+        self.leica_properties.as_ref()
+    }
+
+    /// Philips properties (`philips.<property>`), or `None` if the slide has none.
+    pub fn philips(&self) -> Option<&Philips> {
+        self.philips_properties.as_ref()
     }
 
     /// Print available properties (key, value) (where the value is not `None`).
@@ -76,7 +127,18 @@ impl Properties {
     pub fn print_available(&self) {
         self.openslide_properties.print_available();
         self.tiff_properties.print_available();
-        self.aperio_properties.print_available();
+        if let Some(ref aperio) = self.aperio_properties {
+            aperio.print_available();
+        }
+        if let Some(ref hamamatsu) = self.hamamatsu_properties {
+            hamamatsu.print_available();
+        }
+        if let Some(ref leica) = self.leica_properties {
+            leica.print_available();
+        }
+        if let Some(ref philips) = self.philips_properties {
+            philips.print_available();
+        }
     }
 
     // Openslide properties (the markdown header is on the method above)
@@ -113,6 +175,31 @@ impl Properties {
         self.openslide_properties.comment.clone()
     }
 
+    /// Background color of the slide as red, green and blue components
+    pub fn background_color(&self) -> Option<[u8; 3]> {
+        self.openslide_properties.background_color
+    }
+
+    /// Horizontal position of the non-empty region of the slide, in level 0 pixels
+    pub fn bounds_x(&self) -> Option<u64> {
+        self.openslide_properties.bounds_x
+    }
+
+    /// Vertical position of the non-empty region of the slide, in level 0 pixels
+    pub fn bounds_y(&self) -> Option<u64> {
+        self.openslide_properties.bounds_y
+    }
+
+    /// Width of the non-empty region of the slide, in level 0 pixels
+    pub fn bounds_width(&self) -> Option<u64> {
+        self.openslide_properties.bounds_width
+    }
+
+    /// Height of the non-empty region of the slide, in level 0 pixels
+    pub fn bounds_height(&self) -> Option<u64> {
+        self.openslide_properties.bounds_height
+    }
+
     /// Number of zoom levels
     pub fn level_count(&self) -> Option<u32> {
         self.openslide_properties.level_count
@@ -172,130 +259,330 @@ impl Properties {
 
     /// Slide filename
     pub fn filename(&self) -> Option<String> {
-        self.aperio_properties.filename.clone()
+        self.aperio().and_then(|p| p.filename.clone())
     }
 
     /// Slide image title
     pub fn title(&self) -> Option<String> {
-        self.aperio_properties.title.clone()
+        self.aperio().and_then(|p| p.title.clone())
     }
 
     /// Slide image id
     pub fn image_id(&self) -> Option<String> {
-        self.aperio_properties.image_id.clone()
+        self.aperio().and_then(|p| p.image_id.clone())
     }
 
     /// DSR id
     pub fn dsr_id(&self) -> Option<String> {
-        self.aperio_properties.dsr_id.clone()
+        self.aperio().and_then(|p| p.dsr_id.clone())
     }
 
     /// ScanScope id
     pub fn scan_scope_id(&self) -> Option<String> {
-        self.aperio_properties.scan_scope_id.clone()
+        self.aperio().and_then(|p| p.scan_scope_id.clone())
     }
 
     /// Date of creation (mm/dd/yy)
     pub fn date(&self) -> Option<String> {
         // TODO: Change this to a rust date type
-        self.aperio_properties.date.clone()
+        self.aperio().and_then(|p| p.date.clone())
     }
 
     /// Time of creation (hh:mm:ss)
     pub fn time(&self) -> Option<String> {
         // TODO: Change this to a rust time type
-        self.aperio_properties.time.clone()
+        self.aperio().and_then(|p| p.time.clone())
     }
 
     /// Time zone
     pub fn time_zone(&self) -> Option<String> {
-        self.aperio_properties.time_zone.clone()
+        self.aperio().and_then(|p| p.time_zone.clone())
     }
 
     /// User
     pub fn user(&self) -> Option<String> {
-        self.aperio_properties.user.clone()
+        self.aperio().and_then(|p| p.user.clone())
     }
 
     /// ICC profile
     pub fn icc_profile(&self) -> Option<String> {
-        self.aperio_properties.icc_profile.clone()
+        self.aperio().and_then(|p| p.icc_profile.clone())
     }
 
     /// Parmset
     pub fn parmset(&self) -> Option<String> {
-        self.aperio_properties.parmset.clone()
+        self.aperio().and_then(|p| p.parmset.clone())
     }
 
     /// Slide height
     pub fn original_height(&self) -> Option<u32> {
-        self.aperio_properties.original_height
+        self.aperio().and_then(|p| p.original_height)
     }
 
     /// Slide width
     pub fn original_width(&self) -> Option<u32> {
-        self.aperio_properties.original_height
+        self.aperio().and_then(|p| p.original_height)
     }
 
     pub fn top(&self) -> Option<f32> {
-        self.aperio_properties.top
+        self.aperio().and_then(|p| p.top)
     }
 
     pub fn left(&self) -> Option<f32> {
-        self.aperio_properties.left
+        self.aperio().and_then(|p| p.left)
     }
 
     /// Micrometer per pixel
     pub fn mpp(&self) -> Option<f32> {
-        self.aperio_properties.mpp
+        self.aperio().and_then(|p| p.mpp)
     }
 
     /// Line camera skew
     pub fn line_camera_skew(&self) -> Option<f32> {
-        self.aperio_properties.line_camera_skew
+        self.aperio().and_then(|p| p.line_camera_skew)
     }
 
     /// Line area offset in horizontal(?) direction
     pub fn line_area_x_offset(&self) -> Option<f32> {
-        self.aperio_properties.line_area_x_offset
+        self.aperio().and_then(|p| p.line_area_x_offset)
     }
 
     /// Line area offset in vertical(?) direction
     pub fn line_area_y_offset(&self) -> Option<f32> {
-        self.aperio_properties.line_area_y_offset
+        self.aperio().and_then(|p| p.line_area_y_offset)
     }
 
     /// Focus offset
     pub fn focus_offset(&self) -> Option<f32> {
-        self.aperio_properties.focus_offset
+        self.aperio().and_then(|p| p.focus_offset)
     }
 
     pub fn app_mag(&self) -> Option<u32> {
-        self.aperio_properties.app_mag
+        self.aperio().and_then(|p| p.app_mag)
     }
 
     /// Scan stripe width
     pub fn stripe_width(&self) -> Option<u32> {
-        self.aperio_properties.stripe_width
+        self.aperio().and_then(|p| p.stripe_width)
     }
 
     pub fn filtered(&self) -> Option<u32> {
-        self.aperio_properties.filtered
+        self.aperio().and_then(|p| p.filtered)
     }
 
     pub fn display_color(&self) -> Option<u32> {
-        self.aperio_properties.display_color
+        self.aperio().and_then(|p| p.display_color)
     }
 
     pub fn exposure_time(&self) -> Option<u32> {
-        self.aperio_properties.exposure_time
+        self.aperio().and_then(|p| p.exposure_time)
     }
 
     pub fn exposure_scale(&self) -> Option<f32> {
-        self.aperio_properties.exposure_scale
+        self.aperio().and_then(|p| p.exposure_scale)
     }
 
+    /// # Hamamatsu properties
     pub fn sesson_mode(&self) -> Option<String> {
-        self.aperio_properties.sesson_mode.clone()
+        self.aperio().and_then(|p| p.sesson_mode.clone())
+    }
+
+    // Hamamatsu properties (the markdown header is on the method above)
+
+    /// Magnification of the source lens
+    pub fn source_lens(&self) -> Option<f32> {
+        self.hamamatsu().and_then(|p| p.source_lens)
+    }
+
+    /// Horizontal offset of the scanned area from the slide centre (in nanometers)
+    pub fn x_offset_from_slide_centre(&self) -> Option<i64> {
+        self.hamamatsu().and_then(|p| p.x_offset_from_slide_centre)
+    }
+
+    /// Vertical offset of the scanned area from the slide centre (in nanometers)
+    pub fn y_offset_from_slide_centre(&self) -> Option<i64> {
+        self.hamamatsu().and_then(|p| p.y_offset_from_slide_centre)
+    }
+
+    pub fn objective(&self) -> Option<String> {
+        self.hamamatsu().and_then(|p| p.objective.clone())
+    }
+
+    /// Number of focal layers
+    pub fn no_layers(&self) -> Option<u32> {
+        self.hamamatsu().and_then(|p| p.no_layers)
+    }
+
+    /// Distance between focal layers (in nanometers)
+    pub fn layer_spacing(&self) -> Option<i64> {
+        self.hamamatsu().and_then(|p| p.layer_spacing)
+    }
+
+    /// File name of the macro image (VMS)
+    pub fn macro_image(&self) -> Option<String> {
+        self.hamamatsu().and_then(|p| p.macro_image.clone())
+    }
+
+    /// File name of the first image file (VMS)
+    pub fn image_file(&self) -> Option<String> {
+        self.hamamatsu().and_then(|p| p.image_file.clone())
+    }
+
+    /// File name of the map file (VMS)
+    pub fn map_file(&self) -> Option<String> {
+        self.hamamatsu().and_then(|p| p.map_file.clone())
+    }
+
+    /// File name of the optimisation file (VMS)
+    pub fn optimisation_file(&self) -> Option<String> {
+        self.hamamatsu().and_then(|p| p.optimisation_file.clone())
+    }
+
+    /// Number of JPEG image files in horizontal direction (VMS)
+    pub fn no_jpeg_columns(&self) -> Option<u32> {
+        self.hamamatsu().and_then(|p| p.no_jpeg_columns)
+    }
+
+    /// Number of JPEG image files in vertical direction (VMS)
+    pub fn no_jpeg_rows(&self) -> Option<u32> {
+        self.hamamatsu().and_then(|p| p.no_jpeg_rows)
+    }
+
+    /// Physical width of the scanned area (in nanometers)
+    pub fn physical_width(&self) -> Option<i64> {
+        self.hamamatsu().and_then(|p| p.physical_width)
+    }
+
+    /// Physical height of the scanned area (in nanometers)
+    pub fn physical_height(&self) -> Option<i64> {
+        self.hamamatsu().and_then(|p| p.physical_height)
     }
+
+    /// # Leica properties
+    pub fn reference(&self) -> Option<String> {
+        self.hamamatsu().and_then(|p| p.reference.clone())
+    }
+
+    // Leica properties (the markdown header is on the method above)
+
+    /// Numerical aperture of the objective
+    pub fn aperture(&self) -> Option<f32> {
+        self.leica().and_then(|p| p.aperture)
+    }
+
+    pub fn barcode(&self) -> Option<String> {
+        self.leica().and_then(|p| p.barcode.clone())
+    }
+
+    /// Date and time of the acquisition
+    pub fn creation_date(&self) -> Option<String> {
+        self.leica().and_then(|p| p.creation_date.clone())
+    }
+
+    /// Model of the scanner
+    pub fn device_model(&self) -> Option<String> {
+        self.leica().and_then(|p| p.device_model.clone())
+    }
+
+    /// Version of the scanner
+    pub fn device_version(&self) -> Option<String> {
+        self.leica().and_then(|p| p.device_version.clone())
+    }
+
+    /// Illumination source, e.g. brightfield or fluorescence
+    pub fn illumination_source(&self) -> Option<String> {
+        self.leica().and_then(|p| p.illumination_source.clone())
+    }
+
+    /// Magnification of the objective (`leica.objective`)
+    ///
+    /// # Philips properties
+    pub fn leica_objective(&self) -> Option<f32> {
+        self.leica().and_then(|p| p.objective)
+    }
+
+    // Philips properties (the markdown header is on the method above)
+
+    /// Pixel spacing (row spacing, column spacing) in millimeters
+    pub fn dicom_pixel_spacing(&self) -> Option<(f32, f32)> {
+        self.philips().and_then(|p| p.dicom_pixel_spacing)
+    }
+
+    /// Description of how the image was derived, e.g. the lossy compression that was applied
+    pub fn dicom_derivation_description(&self) -> Option<String> {
+        self.philips().and_then(|p| p.dicom_derivation_description.clone())
+    }
+
+    pub fn dicom_manufacturer(&self) -> Option<String> {
+        self.philips().and_then(|p| p.dicom_manufacturer.clone())
+    }
+
+    pub fn dicom_software_versions(&self) -> Option<String> {
+        self.philips().and_then(|p| p.dicom_software_versions.clone())
+    }
+
+    pub fn dicom_device_serial_number(&self) -> Option<String> {
+        self.philips().and_then(|p| p.dicom_device_serial_number.clone())
+    }
+
+    /// Date and time of the acquisition
+    pub fn dicom_acquisition_datetime(&self) -> Option<String> {
+        self.philips().and_then(|p| p.dicom_acquisition_datetime.clone())
+    }
+
+    /// Bits allocated for each pixel sample
+    pub fn dicom_bits_allocated(&self) -> Option<u32> {
+        self.philips().and_then(|p| p.dicom_bits_allocated)
+    }
+
+    /// Bits stored for each pixel sample
+    pub fn dicom_bits_stored(&self) -> Option<u32> {
+        self.philips().and_then(|p| p.dicom_bits_stored)
+    }
+
+    /// Most significant bit of each pixel sample
+    pub fn dicom_high_bit(&self) -> Option<u32> {
+        self.philips().and_then(|p| p.dicom_high_bit)
+    }
+
+    pub fn dicom_samples_per_pixel(&self) -> Option<u32> {
+        self.philips().and_then(|p| p.dicom_samples_per_pixel)
+    }
+
+    pub fn dicom_photometric_interpretation(&self) -> Option<String> {
+        self.philips().and_then(|p| p.dicom_photometric_interpretation.clone())
+    }
+
+    /// Whether the image has been lossy compressed ("00" or "01")
+    pub fn dicom_lossy_image_compression(&self) -> Option<String> {
+        self.philips().and_then(|p| p.dicom_lossy_image_compression.clone())
+    }
+
+    pub fn dicom_lossy_image_compression_method(&self) -> Option<String> {
+        self.philips().and_then(|p| p.dicom_lossy_image_compression_method.clone())
+    }
+
+    pub fn dicom_lossy_image_compression_ratio(&self) -> Option<f32> {
+        self.philips().and_then(|p| p.dicom_lossy_image_compression_ratio)
+    }
+
+    /// Image type, e.g. "WSI", "LABELIMAGE" or "MACROIMAGE"
+    pub fn pim_dp_image_type(&self) -> Option<String> {
+        self.philips().and_then(|p| p.pim_dp_image_type.clone())
+    }
+
+    /// Slide barcode (base64 encoded)
+    pub fn pim_dp_ufs_barcode(&self) -> Option<String> {
+        self.philips().and_then(|p| p.pim_dp_ufs_barcode.clone())
+    }
+}
+
+/// Parse a property value, with an error naming the property if the value is malformed.
+fn parse_value<T: FromStr>(name: &str, value: &str) -> Result<T, OpenSlideError> {
+    value
+        .parse::<T>()
+        .map_err(|_| OpenSlideError::PropertyParse {
+            key: name.to_string(),
+            value: value.to_string(),
+        })
 }
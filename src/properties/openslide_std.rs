@@ -1,11 +1,14 @@
 //! Openslide properties
 //!
 
-use num::Num;
 use std::collections::HashMap;
 use std::f32;
 use std::u32;
 
+use error::OpenSlideError;
+
+use super::parse_value;
+
 /// Properties defined for every level
 #[derive(Clone, Debug, Default)]
 pub struct LevelProperties {
@@ -64,22 +67,27 @@ impl LevelProperties {
     }
 }
 
-/// Common properties that are available under the name `openslide.<property>` in the HashMap
+/// Standard properties that are available under the name `openslide.<property>` in the HashMap
 /// returned from the `OpenSlide::get_properties()` method.
 #[derive(Clone, Debug)]
-pub struct OpenSlide {
+pub struct Standard {
     pub vendor: Option<String>,
     pub quickhash_1: Option<String>,
     pub mpp_x: Option<f32>,
     pub mpp_y: Option<f32>,
     pub objective_power: Option<u32>,
     pub comment: Option<String>,
+    pub background_color: Option<[u8; 3]>,
+    pub bounds_x: Option<u64>,
+    pub bounds_y: Option<u64>,
+    pub bounds_width: Option<u64>,
+    pub bounds_height: Option<u64>,
     pub level_count: Option<u32>,
     pub levels: Option<Vec<LevelProperties>>,
 }
 
-impl OpenSlide {
-    /// Initialises the OpenSlide properties.
+impl Standard {
+    /// Initialises the standard OpenSlide properties.
     ///
     /// This needs a property map in order to compute the number of levels. This is needed because
     /// of the properties that are listed as `openslide.level[<level>].<property>`.
@@ -112,37 +120,43 @@ impl OpenSlide {
             None => None,
         };
 
-        OpenSlide {
+        Standard {
             vendor: None,
             quickhash_1: None,
             mpp_x: None,
             mpp_y: None,
             objective_power: None,
             comment: None,
+            background_color: None,
+            bounds_x: None,
+            bounds_y: None,
+            bounds_width: None,
+            bounds_height: None,
             level_count,
             levels,
         }
     }
 
-    pub fn parse_property_name(&mut self, name: &str, value: &str) {
+    pub fn parse_property_name(&mut self, name: &str, value: &str) -> Result<(), OpenSlideError> {
         match name {
             "openslide.vendor" => self.vendor = Some(String::from(value)),
             "openslide.quickhash-1" => self.quickhash_1 = Some(String::from(value)),
-            "openslide.mpp-x" => self.mpp_x = Some(f32::from_str_radix(value, 10).unwrap()),
-            "openslide.mpp-y" => self.mpp_y = Some(f32::from_str_radix(value, 10).unwrap()),
-            "openslide.objective-power" => {
-                self.objective_power = Some(u32::from_str_radix(value, 10).unwrap())
-            }
+            "openslide.mpp-x" => self.mpp_x = Some(parse_value(name, value)?),
+            "openslide.mpp-y" => self.mpp_y = Some(parse_value(name, value)?),
+            "openslide.objective-power" => self.objective_power = Some(parse_value(name, value)?),
             "openslide.comment" => self.comment = Some(String::from(value)),
-            "openslide.level-count" => {
-                self.level_count = Some(u32::from_str_radix(value, 10).unwrap())
-            }
+            "openslide.background-color" => self.background_color = Some(parse_color(name, value)?),
+            "openslide.bounds-x" => self.bounds_x = Some(parse_value(name, value)?),
+            "openslide.bounds-y" => self.bounds_y = Some(parse_value(name, value)?),
+            "openslide.bounds-width" => self.bounds_width = Some(parse_value(name, value)?),
+            "openslide.bounds-height" => self.bounds_height = Some(parse_value(name, value)?),
+            "openslide.level-count" => self.level_count = Some(parse_value(name, value)?),
             _ => {
                 if name.contains("level[") {
-                    let level = {
+                    let level: usize = {
                         let starts_with_number = name.split("level[").last().unwrap();
                         let number_as_string = starts_with_number.split("]").nth(0).unwrap();
-                        u32::from_str_radix(number_as_string, 10).unwrap() as usize
+                        parse_value(name, number_as_string)?
                     };
                     match self.levels {
                         Some(ref mut vector) => {
@@ -150,26 +164,24 @@ impl OpenSlide {
                                 .split(&format!("openslide.level[{}].", level))
                                 .last()
                                 .unwrap();
+                            // A level beyond the stated level count has nowhere to go
+                            let level_properties = match vector.get_mut(level) {
+                                Some(level_properties) => level_properties,
+                                None => return Ok(()),
+                            };
                             match last_part {
                                 "downsample" => {
-                                    vector[level].downsample =
-                                        Some(f32::from_str_radix(value, 10).unwrap())
+                                    level_properties.downsample = Some(parse_value(name, value)?)
                                 }
                                 "height" => {
-                                    vector[level].height =
-                                        Some(u32::from_str_radix(value, 10).unwrap())
-                                }
-                                "width" => {
-                                    vector[level].width =
-                                        Some(u32::from_str_radix(value, 10).unwrap())
+                                    level_properties.height = Some(parse_value(name, value)?)
                                 }
+                                "width" => level_properties.width = Some(parse_value(name, value)?),
                                 "tile-height" => {
-                                    vector[level].tile_height =
-                                        Some(u32::from_str_radix(value, 10).unwrap())
+                                    level_properties.tile_height = Some(parse_value(name, value)?)
                                 }
                                 "tile-width" => {
-                                    vector[level].tile_width =
-                                        Some(u32::from_str_radix(value, 10).unwrap())
+                                    level_properties.tile_width = Some(parse_value(name, value)?)
                                 }
                                 //_ => println!(
                                 //    "Could not parse property with name {} and value {}",
@@ -188,6 +200,7 @@ impl OpenSlide {
                 }
             }
         }
+        Ok(())
     }
 
     /// Print available properties (key, value) (where the value is not `None`).
@@ -210,6 +223,21 @@ impl OpenSlide {
         if let Some(ref val) = self.comment {
             println!("Comment: {}", val)
         }
+        if let Some(ref val) = self.background_color {
+            println!("Background color: {:?}", val)
+        }
+        if let Some(ref val) = self.bounds_x {
+            println!("Bounds x: {}", val)
+        }
+        if let Some(ref val) = self.bounds_y {
+            println!("Bounds y: {}", val)
+        }
+        if let Some(ref val) = self.bounds_width {
+            println!("Bounds width: {}", val)
+        }
+        if let Some(ref val) = self.bounds_height {
+            println!("Bounds height: {}", val)
+        }
         if let Some(ref val) = self.level_count {
             println!("Number of levels: {}", val)
         }
@@ -223,6 +251,19 @@ impl OpenSlide {
     // TODO: Consider implementing getter functions and make struct variables private.
 }
 
+/// Parse a color on the form `RRGGBB` (hexadecimal) into its red, green and blue components.
+///
+/// Any other value, e.g. `FFF` or `12345G`, is a `PropertyParse` error.
+fn parse_color(name: &str, value: &str) -> Result<[u8; 3], OpenSlideError> {
+    match u32::from_str_radix(value, 16) {
+        Ok(color) if value.len() == 6 => Ok([(color >> 16) as u8, (color >> 8) as u8, color as u8]),
+        _ => Err(OpenSlideError::PropertyParse {
+            key: name.to_string(),
+            value: value.to_string(),
+        }),
+    }
+}
+
 /// Find the max level from the `openslide.level[<level>].<level-property>` properties.
 fn find_max_level(property_map: &HashMap<String, String>) -> Option<u32> {
     let mut found_levels = Vec::<u32>::new();
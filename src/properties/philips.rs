@@ -0,0 +1,155 @@
+//! Philips properties
+//!
+
+use std::str::FromStr;
+
+use error::OpenSlideError;
+
+/// Vendor specific properties that are available under the name `philips.<property>` in the
+/// HashMap returned from the `OpenSlide::get_properties()` method.
+#[derive(Clone, Debug, Default)]
+pub struct Philips {
+    pub dicom_pixel_spacing: Option<(f32, f32)>,
+    pub dicom_derivation_description: Option<String>,
+    pub dicom_manufacturer: Option<String>,
+    pub dicom_software_versions: Option<String>,
+    pub dicom_device_serial_number: Option<String>,
+    pub dicom_acquisition_datetime: Option<String>,
+    pub dicom_bits_allocated: Option<u32>,
+    pub dicom_bits_stored: Option<u32>,
+    pub dicom_high_bit: Option<u32>,
+    pub dicom_samples_per_pixel: Option<u32>,
+    pub dicom_photometric_interpretation: Option<String>,
+    pub dicom_lossy_image_compression: Option<String>,
+    pub dicom_lossy_image_compression_method: Option<String>,
+    pub dicom_lossy_image_compression_ratio: Option<f32>,
+    pub pim_dp_image_type: Option<String>,
+    pub pim_dp_ufs_barcode: Option<String>,
+}
+
+impl Philips {
+    pub fn parse_property_name(&mut self, name: &str, value: &str) -> Result<(), OpenSlideError> {
+        match name {
+            "philips.DICOM_PIXEL_SPACING" => self.dicom_pixel_spacing = parse_pixel_spacing(value),
+            "philips.DICOM_DERIVATION_DESCRIPTION" => {
+                self.dicom_derivation_description = Some(String::from(value))
+            }
+            "philips.DICOM_MANUFACTURER" => self.dicom_manufacturer = Some(String::from(value)),
+            "philips.DICOM_SOFTWARE_VERSIONS" => {
+                self.dicom_software_versions = Some(String::from(value))
+            }
+            "philips.DICOM_DEVICE_SERIAL_NUMBER" => {
+                self.dicom_device_serial_number = Some(String::from(value))
+            }
+            "philips.DICOM_ACQUISITION_DATETIME" => {
+                self.dicom_acquisition_datetime = Some(String::from(value))
+            }
+            "philips.DICOM_BITS_ALLOCATED" => {
+                self.dicom_bits_allocated = Some(parse_multi_valued(name, value)?)
+            }
+            "philips.DICOM_BITS_STORED" => {
+                self.dicom_bits_stored = Some(parse_multi_valued(name, value)?)
+            }
+            "philips.DICOM_HIGH_BIT" => {
+                self.dicom_high_bit = Some(parse_multi_valued(name, value)?)
+            }
+            "philips.DICOM_SAMPLES_PER_PIXEL" => {
+                self.dicom_samples_per_pixel = Some(parse_multi_valued(name, value)?)
+            }
+            "philips.DICOM_PHOTOMETRIC_INTERPRETATION" => {
+                self.dicom_photometric_interpretation = Some(String::from(value))
+            }
+            "philips.DICOM_LOSSY_IMAGE_COMPRESSION" => {
+                self.dicom_lossy_image_compression = Some(String::from(value))
+            }
+            "philips.DICOM_LOSSY_IMAGE_COMPRESSION_METHOD" => {
+                self.dicom_lossy_image_compression_method = Some(String::from(value))
+            }
+            "philips.DICOM_LOSSY_IMAGE_COMPRESSION_RATIO" => {
+                self.dicom_lossy_image_compression_ratio = Some(parse_multi_valued(name, value)?)
+            }
+            "philips.PIM_DP_IMAGE_TYPE" => self.pim_dp_image_type = Some(String::from(value)),
+            "philips.PIM_DP_UFS_BARCODE" => self.pim_dp_ufs_barcode = Some(String::from(value)),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Print available properties (key, value) (where the value is not `None`).
+    pub fn print_available(&self) {
+        if let Some((row_spacing, col_spacing)) = self.dicom_pixel_spacing {
+            println!("DICOM pixel spacing: {} {}", row_spacing, col_spacing)
+        }
+        if let Some(ref val) = self.dicom_derivation_description {
+            println!("DICOM derivation description: {}", val)
+        }
+        if let Some(ref val) = self.dicom_manufacturer {
+            println!("DICOM manufacturer: {}", val)
+        }
+        if let Some(ref val) = self.dicom_software_versions {
+            println!("DICOM software versions: {}", val)
+        }
+        if let Some(ref val) = self.dicom_device_serial_number {
+            println!("DICOM device serial number: {}", val)
+        }
+        if let Some(ref val) = self.dicom_acquisition_datetime {
+            println!("DICOM acquisition datetime: {}", val)
+        }
+        if let Some(ref val) = self.dicom_bits_allocated {
+            println!("DICOM bits allocated: {}", val)
+        }
+        if let Some(ref val) = self.dicom_bits_stored {
+            println!("DICOM bits stored: {}", val)
+        }
+        if let Some(ref val) = self.dicom_high_bit {
+            println!("DICOM high bit: {}", val)
+        }
+        if let Some(ref val) = self.dicom_samples_per_pixel {
+            println!("DICOM samples per pixel: {}", val)
+        }
+        if let Some(ref val) = self.dicom_photometric_interpretation {
+            println!("DICOM photometric interpretation: {}", val)
+        }
+        if let Some(ref val) = self.dicom_lossy_image_compression {
+            println!("DICOM lossy image compression: {}", val)
+        }
+        if let Some(ref val) = self.dicom_lossy_image_compression_method {
+            println!("DICOM lossy image compression method: {}", val)
+        }
+        if let Some(ref val) = self.dicom_lossy_image_compression_ratio {
+            println!("DICOM lossy image compression ratio: {}", val)
+        }
+        if let Some(ref val) = self.pim_dp_image_type {
+            println!("Image type: {}", val)
+        }
+        if let Some(ref val) = self.pim_dp_ufs_barcode {
+            println!("Barcode: {}", val)
+        }
+    }
+}
+
+/// Parse a DICOM pixel spacing value on the form `"<row spacing>" "<column spacing>"`.
+fn parse_pixel_spacing(value: &str) -> Option<(f32, f32)> {
+    let mut spacings = value
+        .split_whitespace()
+        .map(|part| part.trim_matches('"').parse::<f32>());
+    match (spacings.next(), spacings.next(), spacings.next()) {
+        (Some(Ok(row_spacing)), Some(Ok(col_spacing)), None) => Some((row_spacing, col_spacing)),
+        _ => None,
+    }
+}
+
+/// Parse a DICOM value that can hold several quoted values, e.g. `"3" "3"` with a value for every
+/// frame, like a pixel spacing. Every value has to parse, and the first one is returned.
+fn parse_multi_valued<T: FromStr>(name: &str, value: &str) -> Result<T, OpenSlideError> {
+    let mut values = value
+        .split_whitespace()
+        .map(|part| part.trim_matches('"').parse::<T>());
+    match values.next() {
+        Some(Ok(first)) if values.all(|val| val.is_ok()) => Ok(first),
+        _ => Err(OpenSlideError::PropertyParse {
+            key: name.to_string(),
+            value: value.to_string(),
+        }),
+    }
+}
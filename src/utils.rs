@@ -1,9 +1,15 @@
 //! Misc utility definitions
 
 use byteorder::ByteOrder;
-use failure::{format_err, Error};
-use image::{Rgba, RgbaImage};
+use error::OpenSlideError;
+#[cfg(feature = "image")]
+use image::{
+    imageops, DynamicImage, FilterType, GrayImage, ImageBuffer, ImageFormat, Luma, Pixel, Rgb,
+    RgbImage, Rgba, RgbaImage,
+};
 use num::{Integer, ToPrimitive, Unsigned};
+#[cfg(feature = "image")]
+use std::cmp::Ordering;
 use std::fmt::{Debug, Display};
 
 /// A list of supported formats
@@ -61,6 +67,53 @@ pub enum Format {
     GenericTiledTiff,
 }
 
+impl Format {
+    /// All formats supported by OpenSlide 3.4.1
+    pub fn all() -> Vec<Format> {
+        vec![
+            Format::Aperio,
+            Format::Hamamatsu,
+            Format::Leica,
+            Format::Mirax,
+            Format::Phillips,
+            Format::Sakura,
+            Format::Trestle,
+            Format::Ventana,
+            Format::GenericTiledTiff,
+        ]
+    }
+
+    /// File extensions (including the leading dot) used by this format
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match *self {
+            Format::Aperio => &[".svs", ".tif"],
+            Format::Hamamatsu => &[".vms", ".vmu", ".ndpi"],
+            Format::Leica => &[".scn"],
+            Format::Mirax => &[".mrxs"],
+            Format::Phillips => &[".tiff"],
+            Format::Sakura => &[".svslide"],
+            Format::Trestle => &[".tif"],
+            Format::Ventana => &[".bif", ".tif"],
+            Format::GenericTiledTiff => &[".tif"],
+        }
+    }
+}
+
+/// File extensions (including the leading dot) of all formats supported by OpenSlide 3.4.1
+///
+/// Every extension is listed once, even if it is used by several formats.
+pub fn supported_extensions() -> Vec<&'static str> {
+    let mut extensions = Vec::<&'static str>::new();
+    for format in Format::all() {
+        for extension in format.extensions() {
+            if !extensions.contains(extension) {
+                extensions.push(extension);
+            }
+        }
+    }
+    extensions
+}
+
 /// The different ways the u8 color values are encoded into a u32 value.
 ///
 /// A successfull reading from OpenSlide's `read_region()` will result in a buffer of `u32` with
@@ -68,62 +121,978 @@ pub enum Format {
 /// region. This `u32` value consist of four `u8` values which are the red, green, blue, and alpha
 /// value of a certain pixel. This enum determines in which order to arange these channels within
 /// one element.
-#[derive(Clone, Debug)]
+///
+/// OpenSlide documents its words as `[alpha, red, green, blue]` from the most to least significant
+/// bit, as native `u32` values, so `Native` is correct for the C library on every platform. The
+/// other variants are an escape hatch for library builds or platforms where the channels come out
+/// swapped (e.g. red and blue exchanged in every decoded image).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum WordRepresentation {
+    /// The word layout documented by OpenSlide. This is the same as `BigEndian`.
+    #[default]
+    Native,
     /// From most significant bit to least significant bit: `[alpha, red, green, blue]`
     BigEndian,
     /// From most significant bit to least significant bit: `[blue, green, red, alpha]`
     LittleEndian,
 }
 
+/// The color matrix used to convert from RGB to YUV.
+#[derive(Clone, Copy, Debug)]
+pub enum YuvMatrix {
+    /// ITU-R BT.601, used for standard definition video.
+    Bt601,
+    /// ITU-R BT.709, used for high definition video.
+    Bt709,
+}
+
+impl YuvMatrix {
+    /// The (red, blue) luma coefficients (Kr, Kb) of this matrix.
+    #[cfg(feature = "image")]
+    fn coefficients(self) -> (f64, f64) {
+        match self {
+            YuvMatrix::Bt601 => (0.299, 0.114),
+            YuvMatrix::Bt709 => (0.2126, 0.0722),
+        }
+    }
+}
+
+/// How `rgba_to_gray()` converts fully transparent pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TransparentGray {
+    /// Composite them over the background color, like partially transparent pixels.
+    #[default]
+    Background,
+    /// Map them to a fixed value, e.g. so that they can be masked out afterwards.
+    Sentinel(u8),
+}
+
+/// The orientation of stored pixels, as in the TIFF and EXIF `Orientation` tag
+///
+/// Every variant is named after the transform that makes the stored image upright, see
+/// `orient()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Orientation {
+    /// Upright as stored (tag value 1)
+    #[default]
+    Normal,
+    /// Mirrored left to right (tag value 2)
+    FlipHorizontal,
+    /// Upside down (tag value 3)
+    Rotate180,
+    /// Mirrored top to bottom (tag value 4)
+    FlipVertical,
+    /// Rows and columns swapped (tag value 5)
+    Transpose,
+    /// Upright when rotated 90 degrees clockwise (tag value 6)
+    Rotate90,
+    /// Rows and columns swapped, and upside down (tag value 7)
+    Transverse,
+    /// Upright when rotated 270 degrees clockwise (tag value 8)
+    Rotate270,
+}
+
+impl Orientation {
+    /// Get the orientation with the given TIFF `Orientation` tag value, if it is valid (1 to 8).
+    pub fn from_tiff_tag(value: u16) -> Option<Orientation> {
+        match value {
+            1 => Some(Orientation::Normal),
+            2 => Some(Orientation::FlipHorizontal),
+            3 => Some(Orientation::Rotate180),
+            4 => Some(Orientation::FlipVertical),
+            5 => Some(Orientation::Transpose),
+            6 => Some(Orientation::Rotate90),
+            7 => Some(Orientation::Transverse),
+            8 => Some(Orientation::Rotate270),
+            _ => None,
+        }
+    }
+
+    /// Check whether making the image upright swaps its width and height.
+    pub fn swaps_dimensions(self) -> bool {
+        matches!(
+            self,
+            Orientation::Transpose
+                | Orientation::Rotate90
+                | Orientation::Transverse
+                | Orientation::Rotate270
+        )
+    }
+}
+
+/// The memory layout of the data from `decode_buffer_tensor()`
+#[cfg(feature = "tensor")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TensorLayout {
+    /// Shape `[1, 3, height, width]`: one plane per channel
+    Nchw,
+    /// Shape `[1, height, width, 3]`: the channels of every pixel together
+    Nhwc,
+}
+
+/// Un-premultiplied 8 bit RGBA pixels, independent of the `image` crate
+///
+/// `data` holds `[red, green, blue, alpha]` for every pixel, in row major order. With the `image`
+/// feature (enabled by default), it converts to an `image::RgbaImage` with `into_rgba_image()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rgba8Buffer {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// `4 * width * height` channel values
+    pub data: Vec<u8>,
+}
+
+impl Rgba8Buffer {
+    /// Convert to an `image::RgbaImage`, without copying the pixels.
+    #[cfg(feature = "image")]
+    pub fn into_rgba_image(self) -> Result<RgbaImage, OpenSlideError> {
+        let (width, height, num_values) = (self.width, self.height, self.data.len());
+        RgbaImage::from_raw(width, height, self.data).ok_or_else(|| {
+            OpenSlideError::InvalidArgument(format!(
+                "{} values do not fill an Rgba image of (width, height) ({}, {})",
+                num_values, width, height
+            ))
+        })
+    }
+}
+
+/// This function takes a buffer, as the one obtained from openslide::read_region, and decodes into
+/// an Rgba buffer.
+///
+/// OpenSlide returns premultiplied colors. They are un-premultiplied here: the red, green and
+/// blue channels of pixels with a partial alpha (neither 0 nor 255) are divided by the alpha
+/// fraction, as `255 * value / alpha` rounded down and clamped to 255. Use `decode_buffer_f32`
+/// to keep the premultiplied colors.
+///
+/// This does not depend on the `image` crate; `decode_buffer` is the same, but returns an
+/// `image::RgbaImage`.
+pub fn decode_buffer_rgba8<T: Unsigned + Integer + ToPrimitive + Debug + Display + Clone + Copy>(
+    buffer: &[u32],
+    height: T,
+    width: T,
+    word_representation: WordRepresentation,
+) -> Result<Rgba8Buffer, OpenSlideError> {
+    let (height_u32, width_u32, num_pixels) = checked_num_pixels(buffer, height, width)?;
+
+    let mut data = Vec::with_capacity(4 * num_pixels);
+    for &value in &buffer[..num_pixels] {
+        let [alpha, mut red, mut green, mut blue] = split_word(value, word_representation);
+
+        if alpha != 0 && alpha != 255 {
+            red = unpremultiply(red, alpha);
+            green = unpremultiply(green, alpha);
+            blue = unpremultiply(blue, alpha);
+        }
+
+        data.extend_from_slice(&[red, green, blue, alpha]);
+    }
+
+    Ok(Rgba8Buffer {
+        width: width_u32,
+        height: height_u32,
+        data,
+    })
+}
+
+/// This function takes a buffer, as the one obtained from openslide::read_region, and decodes into
+/// an image of normalized, premultiplied floating point RGBA values in `[0, 1]`.
+///
+/// Unlike `decode_buffer`, the colors are not un-premultiplied, which makes the result suitable
+/// for alpha compositing.
+#[cfg(feature = "image")]
+pub fn decode_buffer_f32<T: Unsigned + Integer + ToPrimitive + Debug + Display + Clone + Copy>(
+    buffer: &[u32],
+    height: T,
+    width: T,
+    word_representation: WordRepresentation,
+) -> Result<ImageBuffer<Rgba<f32>, Vec<f32>>, OpenSlideError> {
+    let (height_u32, width_u32, num_pixels) = checked_num_pixels(buffer, height, width)?;
+
+    let mut data = Vec::with_capacity(4 * num_pixels);
+    for &value in &buffer[..num_pixels] {
+        let [alpha, red, green, blue] = split_word(value, word_representation);
+        data.extend(
+            [red, green, blue, alpha]
+                .iter()
+                .map(|channel| f32::from(*channel) / 255.0),
+        );
+    }
+
+    ImageBuffer::from_raw(width_u32, height_u32, data).ok_or_else(|| {
+        OpenSlideError::InvalidArgument(String::from(
+            "Unable to create an image from the decoded buffer",
+        ))
+    })
+}
+
+/// This function takes a buffer, as the one obtained from openslide::read_region, and decodes into
+/// an 8 bit grayscale image.
+///
+/// The colors are un-premultiplied like in `decode_buffer`, and converted to luminance with the
+/// Rec.601 weights (0.299, 0.587, 0.114), without an intermediate Rgba image. Alpha is dropped,
+/// so fully transparent pixels are black; see `rgba_to_gray()` to composite over a background.
+#[cfg(feature = "image")]
+pub fn decode_buffer_luma<T: Unsigned + Integer + ToPrimitive + Debug + Display + Clone + Copy>(
+    buffer: &[u32],
+    height: T,
+    width: T,
+    word_representation: WordRepresentation,
+) -> Result<GrayImage, OpenSlideError> {
+    let (height_u32, width_u32, num_pixels) = checked_num_pixels(buffer, height, width)?;
+
+    let (kr, kb) = YuvMatrix::Bt601.coefficients();
+    let kg = 1.0 - kr - kb;
+    let mut data = Vec::with_capacity(num_pixels);
+    for &value in &buffer[..num_pixels] {
+        let [alpha, mut red, mut green, mut blue] = split_word(value, word_representation);
+        if alpha != 0 && alpha != 255 {
+            red = unpremultiply(red, alpha);
+            green = unpremultiply(green, alpha);
+            blue = unpremultiply(blue, alpha);
+        }
+        let luma = kr * f64::from(red) + kg * f64::from(green) + kb * f64::from(blue);
+        data.push(luma.round().min(255.0) as u8);
+    }
+
+    ImageBuffer::from_raw(width_u32, height_u32, data).ok_or_else(|| {
+        OpenSlideError::InvalidArgument(String::from(
+            "Unable to create an image from the decoded buffer",
+        ))
+    })
+}
+
+/// This function takes a buffer, as the one obtained from openslide::read_region, and decodes into
+/// tightly packed RGB bytes composited over a `[red, green, blue]` background.
+///
+/// The bytes are `[red, green, blue]` for every pixel, in row major order, e.g. to copy straight
+/// into a framebuffer. The premultiplied colors are composited directly, so this does not depend
+/// on the `image` crate, and is not affected by the rounding of un-premultiplying.
+pub fn decode_buffer_rgb_vec<
+    T: Unsigned + Integer + ToPrimitive + Debug + Display + Clone + Copy,
+>(
+    buffer: &[u32],
+    height: T,
+    width: T,
+    word_representation: WordRepresentation,
+    background: [u8; 3],
+) -> Result<Vec<u8>, OpenSlideError> {
+    let (_, _, num_pixels) = checked_num_pixels(buffer, height, width)?;
+
+    let mut data = Vec::with_capacity(3 * num_pixels);
+    for &value in &buffer[..num_pixels] {
+        let [alpha, red, green, blue] = split_word(value, word_representation);
+        let transparency = 255 - u32::from(alpha);
+        for (channel, background) in [red, green, blue].iter().zip(background.iter()) {
+            let composited = 255 * u32::from(*channel) + u32::from(*background) * transparency;
+            data.push(((composited + 127) / 255).min(255) as u8);
+        }
+    }
+    Ok(data)
+}
+
+/// This function takes a buffer, as the one obtained from openslide::read_region, and decodes into
+/// flat floating point RGB data in the given tensor layout, along with its shape.
+///
+/// The colors are un-premultiplied like in `decode_buffer`, scaled to `[0, 1]`, and with
+/// `normalize = Some((mean, std))` mapped to `(value - mean) / std`, all in one pass. Alpha is
+/// dropped. The shape is in the order of the layout, with a batch size of 1.
+#[cfg(feature = "tensor")]
+pub fn decode_buffer_tensor<
+    T: Unsigned + Integer + ToPrimitive + Debug + Display + Clone + Copy,
+>(
+    buffer: &[u32],
+    height: T,
+    width: T,
+    word_representation: WordRepresentation,
+    layout: TensorLayout,
+    normalize: Option<(f32, f32)>,
+) -> Result<(Vec<f32>, [usize; 4]), OpenSlideError> {
+    let (height_u32, width_u32, num_pixels) = checked_num_pixels(buffer, height, width)?;
+    let (height_usize, width_usize) = (height_u32 as usize, width_u32 as usize);
+    let (mean, std) = normalize.unwrap_or((0.0, 1.0));
+    if !(std.is_finite() && std != 0.0) {
+        return Err(OpenSlideError::InvalidArgument(format!(
+            "Standard deviation must be finite and non-zero, got {}",
+            std
+        )));
+    }
+
+    let mut data = vec![0.0; 3 * num_pixels];
+    for (index, &value) in buffer[..num_pixels].iter().enumerate() {
+        let [alpha, mut red, mut green, mut blue] = split_word(value, word_representation);
+        if alpha != 0 && alpha != 255 {
+            red = unpremultiply(red, alpha);
+            green = unpremultiply(green, alpha);
+            blue = unpremultiply(blue, alpha);
+        }
+        for (channel, value) in [red, green, blue].iter().enumerate() {
+            let position = match layout {
+                TensorLayout::Nchw => channel * num_pixels + index,
+                TensorLayout::Nhwc => 3 * index + channel,
+            };
+            data[position] = (f32::from(*value) / 255.0 - mean) / std;
+        }
+    }
+
+    let shape = match layout {
+        TensorLayout::Nchw => [1, 3, height_usize, width_usize],
+        TensorLayout::Nhwc => [1, height_usize, width_usize, 3],
+    };
+    Ok((data, shape))
+}
+
+/// Convert the dimensions of a region to `u32` and check that the buffer holds its pixels.
+///
+/// Returns the height, the width and the number of pixels of the region.
+fn checked_num_pixels<T: ToPrimitive + Display>(
+    buffer: &[u32],
+    height: T,
+    width: T,
+) -> Result<(u32, u32, usize), OpenSlideError> {
+    let height_u32 = height.to_u32().ok_or_else(|| {
+        OpenSlideError::InvalidArgument(String::from("Conversion to primitive error"))
+    })?;
+    let width_u32 = width.to_u32().ok_or_else(|| {
+        OpenSlideError::InvalidArgument(String::from("Conversion to primitive error"))
+    })?;
+    let num_pixels = height_u32 as usize * width_u32 as usize;
+    if buffer.len() < num_pixels {
+        return Err(OpenSlideError::InvalidArgument(format!(
+            "Buffer has {} values, but {} x {} = {} are needed",
+            buffer.len(),
+            height,
+            width,
+            num_pixels
+        )));
+    }
+    Ok((height_u32, width_u32, num_pixels))
+}
+
+/// Un-premultiply a color channel with a partial alpha, as `255 * value / alpha` rounded down.
+///
+/// This is the integer division of the OpenSlide Python bindings, so decoded pixels are the same
+/// as theirs. The result is clamped to 255 for invalid words with a channel above alpha.
+fn unpremultiply(value: u8, alpha: u8) -> u8 {
+    (255 * u32::from(value) / u32::from(alpha)).min(255) as u8
+}
+
+/// Split a word of a buffer from OpenSlide into its `[alpha, red, green, blue]` channels.
+///
+/// Writing the word with the byte order of its representation puts alpha in the first byte, as
+/// alpha is the most significant byte of a `BigEndian` word and the least significant byte of a
+/// `LittleEndian` word.
+fn split_word(value: u32, word_representation: WordRepresentation) -> [u8; 4] {
+    let mut buf = [0; 4];
+    match word_representation {
+        WordRepresentation::Native | WordRepresentation::BigEndian => {
+            byteorder::BigEndian::write_u32(&mut buf, value)
+        }
+        WordRepresentation::LittleEndian => byteorder::LittleEndian::write_u32(&mut buf, value),
+    };
+    buf
+}
+
 /// This function takes a buffer, as the one obtained from openslide::read_region, and decodes into
 /// an Rgba image buffer.
+///
+/// The colors are un-premultiplied like in `decode_buffer_rgba8`.
+#[cfg(feature = "image")]
 pub fn decode_buffer<T: Unsigned + Integer + ToPrimitive + Debug + Display + Clone + Copy>(
     buffer: &Vec<u32>,
     height: T,
     width: T,
     word_representation: WordRepresentation,
-) -> Result<RgbaImage, Error> {
-    let mut rgba_image = RgbaImage::new(
-        width
-            .to_u32()
-            .ok_or(format_err!("Conversion to primitive error"))?,
-        height
-            .to_u32()
-            .ok_or(format_err!("Conversion to primitive error"))?,
-    );
-
-    for (col, row, pixel) in rgba_image.enumerate_pixels_mut() {
-        let curr_pos = row * width
-            .to_u32()
-            .ok_or(format_err!("Conversion to primitive error"))?
-            + col;
-        let value = buffer[curr_pos as usize];
-
-        let mut buf = [0; 4];
-        match word_representation {
-            WordRepresentation::BigEndian => byteorder::BigEndian::write_u32(&mut buf, value),
-            WordRepresentation::LittleEndian => byteorder::BigEndian::write_u32(&mut buf, value),
+) -> Result<RgbaImage, OpenSlideError> {
+    decode_buffer_rgba8(buffer, height, width, word_representation)?.into_rgba_image()
+}
+
+/// Make an image stored with the given orientation upright.
+///
+/// The width and height of the result are swapped if `orientation.swaps_dimensions()`.
+#[cfg(feature = "image")]
+pub fn orient(image: &RgbaImage, orientation: Orientation) -> RgbaImage {
+    match orientation {
+        Orientation::Normal => image.clone(),
+        Orientation::FlipHorizontal => imageops::flip_horizontal(image),
+        Orientation::Rotate180 => imageops::rotate180(image),
+        Orientation::FlipVertical => imageops::flip_vertical(image),
+        Orientation::Transpose => imageops::flip_horizontal(&imageops::rotate90(image)),
+        Orientation::Rotate90 => imageops::rotate90(image),
+        Orientation::Transverse => imageops::flip_vertical(&imageops::rotate90(image)),
+        Orientation::Rotate270 => imageops::rotate270(image),
+    }
+}
+
+/// Downsample an image by an integer factor using a box filter.
+///
+/// Every output pixel is the (rounded) mean of a `factor` x `factor` block of input pixels. Input
+/// pixels at the right and bottom edges that do not fill a whole block are discarded.
+#[cfg(feature = "image")]
+pub fn box_downsample(image: &RgbaImage, factor: u32) -> Result<RgbaImage, OpenSlideError> {
+    if factor == 0 {
+        return Err(OpenSlideError::InvalidArgument(String::from(
+            "Box filter factor must be positive, got 0",
+        )));
+    }
+
+    let (width, height) = image.dimensions();
+    let mut downsampled = RgbaImage::new(width / factor, height / factor);
+    let block_size = u64::from(factor) * u64::from(factor);
+
+    for (col, row, pixel) in downsampled.enumerate_pixels_mut() {
+        let mut sums = [0u64; 4];
+        for block_row in 0..factor {
+            for block_col in 0..factor {
+                let source = image.get_pixel(col * factor + block_col, row * factor + block_row);
+                for (sum, value) in sums.iter_mut().zip(source.data.iter()) {
+                    *sum += u64::from(*value);
+                }
+            }
+        }
+
+        let mut mean = [0u8; 4];
+        for (value, sum) in mean.iter_mut().zip(sums.iter()) {
+            *value = ((sum + block_size / 2) / block_size) as u8;
+        }
+        *pixel = Rgba(mean);
+    }
+
+    Ok(downsampled)
+}
+
+/// Shrink an image to fit within `max_width` x `max_height`, preserving its aspect ratio.
+///
+/// Images that already fit are returned unchanged; images are never enlarged. Each dimension is
+/// rounded to the nearest pixel, and is at least 1.
+#[cfg(feature = "image")]
+pub fn resize_to_fit(image: &RgbaImage, max_width: u32, max_height: u32) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    if width <= max_width && height <= max_height {
+        return image.clone();
+    }
+
+    let scale = (f64::from(max_width) / f64::from(width))
+        .min(f64::from(max_height) / f64::from(height));
+    let new_width = ((f64::from(width) * scale).round() as u32).max(1);
+    let new_height = ((f64::from(height) * scale).round() as u32).max(1);
+    imageops::resize(image, new_width, new_height, FilterType::Triangle)
+}
+
+/// The per-pixel difference between two images of the same dimensions.
+///
+/// Every color channel is `image - base + 128`, clamped to `[0, 255]`, so that no difference is
+/// mid gray. The alpha channel is that of `image`.
+#[cfg(feature = "image")]
+pub fn difference_image(image: &RgbaImage, base: &RgbaImage) -> Result<RgbaImage, OpenSlideError> {
+    if image.dimensions() != base.dimensions() {
+        return Err(OpenSlideError::InvalidArgument(format!(
+            "Images have different (width, height), {:?} and {:?}",
+            image.dimensions(),
+            base.dimensions(),
+        )));
+    }
+
+    let mut difference = image.clone();
+    for (pixel, base_pixel) in difference.pixels_mut().zip(base.pixels()) {
+        for channel in 0..3 {
+            let value = i16::from(pixel.data[channel]) - i16::from(base_pixel.data[channel]) + 128;
+            pixel.data[channel] = value.clamp(0, 255) as u8;
+        }
+    }
+    Ok(difference)
+}
+
+/// Linearly blend two images of the same dimensions, with `weight` (in `[0, 1]`) for `other`.
+///
+/// Every channel, including alpha, is `(1 - weight) * image + weight * other`, rounded.
+#[cfg(feature = "image")]
+pub fn blend(
+    image: &RgbaImage,
+    other: &RgbaImage,
+    weight: f64,
+) -> Result<RgbaImage, OpenSlideError> {
+    if image.dimensions() != other.dimensions() {
+        return Err(OpenSlideError::InvalidArgument(format!(
+            "Images have different (width, height), {:?} and {:?}",
+            image.dimensions(),
+            other.dimensions(),
+        )));
+    }
+    if !(0.0..=1.0).contains(&weight) {
+        return Err(OpenSlideError::InvalidArgument(format!(
+            "Blend weight must be in [0, 1], got {}",
+            weight
+        )));
+    }
+
+    let mut blended = image.clone();
+    for (pixel, other_pixel) in blended.pixels_mut().zip(other.pixels()) {
+        for (value, other_value) in pixel.data.iter_mut().zip(other_pixel.data.iter()) {
+            let mixed = (1.0 - weight) * f64::from(*value) + weight * f64::from(*other_value);
+            *value = mixed.round() as u8;
+        }
+    }
+    Ok(blended)
+}
+
+/// Build the lookup table of a gamma curve, mapping `value` to `255 * (value / 255)^gamma`.
+///
+/// The entries are rounded. A gamma below 1 brightens, and a gamma above 1 darkens. Returns an
+/// error unless `gamma` is positive and finite.
+pub fn gamma_lut(gamma: f32) -> Result<[u8; 256], OpenSlideError> {
+    if !(gamma.is_finite() && gamma > 0.0) {
+        return Err(OpenSlideError::InvalidArgument(format!(
+            "Gamma must be positive and finite, got {}",
+            gamma
+        )));
+    }
+    let mut lut = [0u8; 256];
+    for (value, entry) in lut.iter_mut().enumerate() {
+        *entry = (255.0 * (value as f32 / 255.0).powf(gamma)).round() as u8;
+    }
+    Ok(lut)
+}
+
+/// Apply a gamma curve (see `gamma_lut()`) to the color channels of an image, keeping alpha.
+#[cfg(feature = "image")]
+pub fn apply_gamma(image: &mut RgbaImage, gamma: f32) -> Result<(), OpenSlideError> {
+    let lut = gamma_lut(gamma)?;
+    for pixel in image.pixels_mut() {
+        for value in pixel.data[..3].iter_mut() {
+            *value = lut[*value as usize];
+        }
+    }
+    Ok(())
+}
+
+/// Optical density vectors (red, green, blue) of hematoxylin, eosin and a residual, for
+/// `color_deconvolve()`
+///
+/// Hematoxylin and eosin are from Ruifrok, A. C. and Johnston, D. A., "Quantification of
+/// histochemical staining by color deconvolution", 2001. The residual channel is the complement
+/// of the two, as in the ImageJ colour deconvolution plugin.
+pub const HE_STAIN_MATRIX: [[f32; 3]; 3] = [
+    [0.650, 0.704, 0.286],
+    [0.072, 0.990, 0.105],
+    [0.622, 0.0, 0.783],
+];
+
+/// Separate an Rgb image into the images of three stains, by color deconvolution.
+///
+/// Every row of `stain_matrix` is the optical density vector (red, green, blue) of a stain (e.g.
+/// `HE_STAIN_MATRIX`), and is normalized before use. The optical density of every pixel is
+/// decomposed into the amounts of the stains, and every output image shows the pixel as if it
+/// only had that stain: 255 where there is none of it, and darker with more of it. Returns an
+/// error if the stain vectors are linearly dependent.
+#[cfg(feature = "image")]
+pub fn color_deconvolve(
+    image: &RgbImage,
+    stain_matrix: [[f32; 3]; 3],
+) -> Result<[GrayImage; 3], OpenSlideError> {
+    let mut stains = [[0f64; 3]; 3];
+    for (stain, row) in stains.iter_mut().zip(stain_matrix.iter()) {
+        let norm = row.iter().map(|value| f64::from(*value).powi(2)).sum::<f64>().sqrt();
+        if norm == 0.0 {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Stain matrix {:?} has a zero row",
+                stain_matrix
+            )));
+        }
+        for (normalized, value) in stain.iter_mut().zip(row.iter()) {
+            *normalized = f64::from(*value) / norm;
+        }
+    }
+    let inverse = match invert_3x3(&stains) {
+        Some(inverse) => inverse,
+        None => {
+            return Err(OpenSlideError::InvalidArgument(format!(
+                "Stain matrix {:?} is singular",
+                stain_matrix
+            )))
+        }
+    };
+
+    let (width, height) = image.dimensions();
+    let mut separated = [
+        GrayImage::new(width, height),
+        GrayImage::new(width, height),
+        GrayImage::new(width, height),
+    ];
+    for (col, row, pixel) in image.enumerate_pixels() {
+        let mut optical_density = [0f64; 3];
+        for (density, value) in optical_density.iter_mut().zip(pixel.data.iter()) {
+            *density = -(f64::from((*value).max(1)) / 255.0).log10();
+        }
+        for (stain, stain_image) in separated.iter_mut().enumerate() {
+            let amount = (0..3)
+                .map(|channel| optical_density[channel] * inverse[channel][stain])
+                .sum::<f64>();
+            let value = (255.0 * 10f64.powf(-amount)).round().clamp(0.0, 255.0) as u8;
+            stain_image.put_pixel(col, row, Luma([value]));
+        }
+    }
+
+    Ok(separated)
+}
+
+/// The inverse of a 3 x 3 matrix, or `None` if it is singular.
+#[cfg(feature = "image")]
+fn invert_3x3(matrix: &[[f64; 3]; 3]) -> Option<[[f64; 3]; 3]> {
+    let m = matrix;
+    let cofactor = |row: usize, col: usize| {
+        let (r0, r1) = ((row + 1) % 3, (row + 2) % 3);
+        let (c0, c1) = ((col + 1) % 3, (col + 2) % 3);
+        m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+    };
+    let determinant = (0..3).map(|col| m[0][col] * cofactor(0, col)).sum::<f64>();
+    if determinant.abs() < 1e-12 {
+        return None;
+    }
+
+    let mut inverse = [[0f64; 3]; 3];
+    for (row, inverse_row) in inverse.iter_mut().enumerate() {
+        for (col, value) in inverse_row.iter_mut().enumerate() {
+            *value = cofactor(col, row) / determinant;
+        }
+    }
+    Some(inverse)
+}
+
+/// Split an Rgba image into an Rgb image and a grayscale alpha mask.
+#[cfg(feature = "image")]
+pub fn split_alpha(image: &RgbaImage) -> (RgbImage, GrayImage) {
+    let (width, height) = image.dimensions();
+    let mut rgb_image = RgbImage::new(width, height);
+    let mut alpha_mask = GrayImage::new(width, height);
+
+    for (col, row, pixel) in image.enumerate_pixels() {
+        let [red, green, blue, alpha] = pixel.data;
+        rgb_image.put_pixel(col, row, Rgb([red, green, blue]));
+        alpha_mask.put_pixel(col, row, Luma([alpha]));
+    }
+
+    (rgb_image, alpha_mask)
+}
+
+/// Convert an (un-premultiplied) Rgba image to a grayscale image.
+///
+/// Pixels are composited over `background` before the luminance is computed, so transparent
+/// pixels get the luminance of the background rather than of black. Fully transparent pixels are
+/// handled according to `transparent`.
+#[cfg(feature = "image")]
+pub fn rgba_to_gray(
+    image: &RgbaImage,
+    background: Rgb<u8>,
+    transparent: TransparentGray,
+) -> GrayImage {
+    let (width, height) = image.dimensions();
+    let mut gray_image = GrayImage::new(width, height);
+
+    for (col, row, pixel) in image.enumerate_pixels() {
+        let value = match transparent {
+            TransparentGray::Sentinel(sentinel) if pixel.data[3] == 0 => sentinel,
+            _ => composite_pixel(pixel, background).to_luma().data[0],
         };
-        let [alpha, mut red, mut green, mut blue] = buf;
+        gray_image.put_pixel(col, row, Luma([value]));
+    }
 
-        if alpha != 0 && alpha != 255 {
-            red = (red as f32 * (255.0 / alpha as f32))
-                .round()
-                .max(0.0)
-                .min(255.0) as u8;
-            green = (green as f32 * (255.0 / alpha as f32))
-                .round()
-                .max(0.0)
-                .min(255.0) as u8;
-            blue = (blue as f32 * (255.0 / alpha as f32))
-                .round()
-                .max(0.0)
-                .min(255.0) as u8;
+    gray_image
+}
+
+/// Composite an (un-premultiplied) Rgba image over a background color.
+#[cfg(feature = "image")]
+pub fn composite_over(image: &RgbaImage, background: Rgb<u8>) -> RgbImage {
+    let (width, height) = image.dimensions();
+    let mut rgb_image = RgbImage::new(width, height);
+    for (col, row, pixel) in image.enumerate_pixels() {
+        rgb_image.put_pixel(col, row, composite_pixel(pixel, background));
+    }
+    rgb_image
+}
+
+/// Composite an (un-premultiplied) Rgba pixel over a background color.
+#[cfg(feature = "image")]
+fn composite_pixel(pixel: &Rgba<u8>, background: Rgb<u8>) -> Rgb<u8> {
+    let alpha = u32::from(pixel.data[3]);
+    let mut composited = [0u8; 3];
+    for (channel, value) in composited.iter_mut().enumerate() {
+        let foreground = u32::from(pixel.data[channel]) * alpha;
+        let background = u32::from(background.data[channel]) * (255 - alpha);
+        *value = ((foreground + background + 127) / 255) as u8;
+    }
+    Rgb(composited)
+}
+
+/// The saturation (as in the HSV color model) of a pixel, in `[0, 1]`.
+#[cfg(feature = "image")]
+pub fn saturation(pixel: &Rgba<u8>) -> f64 {
+    let [red, green, blue, _] = pixel.data;
+    let max = red.max(green).max(blue);
+    let min = red.min(green).min(blue);
+    if max == 0 {
+        0.0
+    } else {
+        f64::from(max - min) / f64::from(max)
+    }
+}
+
+/// The fraction of pixels in an image that are foreground (tissue).
+///
+/// A pixel is foreground if it is not transparent, and its saturation is above
+/// `saturation_threshold`. Slide background is white or light gray, which has a low saturation.
+/// Returns 0 for an empty image.
+#[cfg(feature = "image")]
+pub fn foreground_fraction(image: &RgbaImage, saturation_threshold: f64) -> f64 {
+    let num_pixels = u64::from(image.width()) * u64::from(image.height());
+    if num_pixels == 0 {
+        return 0.0;
+    }
+
+    let num_foreground = image
+        .pixels()
+        .filter(|pixel| pixel.data[3] != 0 && saturation(pixel) > saturation_threshold)
+        .count();
+    num_foreground as f64 / num_pixels as f64
+}
+
+/// Convert an Rgba image to NV12, returning the (Y plane, interleaved UV plane).
+///
+/// The Y plane has one byte per pixel. The UV plane has one (U, V) pair per 2 x 2 block of
+/// pixels, which is the mean over the pixels of the block (blocks at the right and bottom edges
+/// of images with odd dimensions have fewer pixels). The values are in the limited (video) range,
+/// 16 to 235 for Y and 16 to 240 for U and V. The alpha channel is ignored.
+#[cfg(feature = "image")]
+pub fn rgba_to_nv12(image: &RgbaImage, matrix: YuvMatrix) -> (Vec<u8>, Vec<u8>) {
+    let (width, height) = image.dimensions();
+    let (kr, kb) = matrix.coefficients();
+    let chroma_width = width.div_ceil(2);
+    let chroma_height = height.div_ceil(2);
+
+    let mut y_plane = Vec::<u8>::with_capacity((width * height) as usize);
+    let mut chroma_sums = vec![(0.0, 0.0, 0u32); (chroma_width * chroma_height) as usize];
+    for (col, row, pixel) in image.enumerate_pixels() {
+        let red = f64::from(pixel.data[0]) / 255.0;
+        let green = f64::from(pixel.data[1]) / 255.0;
+        let blue = f64::from(pixel.data[2]) / 255.0;
+
+        let luma = kr * red + (1.0 - kr - kb) * green + kb * blue;
+        y_plane.push((16.0 + 219.0 * luma).round() as u8);
+
+        let chroma = &mut chroma_sums[((row / 2) * chroma_width + col / 2) as usize];
+        chroma.0 += (blue - luma) / (2.0 * (1.0 - kb));
+        chroma.1 += (red - luma) / (2.0 * (1.0 - kr));
+        chroma.2 += 1;
+    }
+
+    let mut uv_plane = Vec::<u8>::with_capacity(chroma_sums.len() * 2);
+    for (u_sum, v_sum, count) in chroma_sums {
+        let count = f64::from(count);
+        uv_plane.push((128.0 + 224.0 * u_sum / count).round() as u8);
+        uv_plane.push((128.0 + 224.0 * v_sum / count).round() as u8);
+    }
+
+    (y_plane, uv_plane)
+}
+
+/// Compute a 64 bit perceptual hash (pHash) of an image.
+///
+/// The image is resized to 32 x 32 grayscale pixels, and transformed with a 2D discrete cosine
+/// transform. Each bit of the hash tells whether one of the 8 x 8 lowest frequency coefficients is
+/// above their median (row-major, most significant bit first). Similar images have hashes with a
+/// small `hamming_distance()`.
+#[cfg(feature = "image")]
+pub fn perceptual_hash(image: &RgbaImage) -> u64 {
+    const SIZE: usize = 32;
+    const LOW_FREQUENCIES: usize = 8;
+
+    let small = imageops::resize(image, SIZE as u32, SIZE as u32, FilterType::Triangle);
+    let gray = imageops::grayscale(&small);
+    let pixels: Vec<f64> = gray.pixels().map(|pixel| f64::from(pixel.data[0])).collect();
+
+    let cosines: Vec<f64> = (0..LOW_FREQUENCIES * SIZE)
+        .map(|index| {
+            let (frequency, position) = (index / SIZE, index % SIZE);
+            ((2 * position + 1) as f64 * frequency as f64 * std::f64::consts::PI
+                / (2 * SIZE) as f64)
+                .cos()
+        })
+        .collect();
+    let mut coefficients = Vec::<f64>::with_capacity(LOW_FREQUENCIES * LOW_FREQUENCIES);
+    for row_frequency in 0..LOW_FREQUENCIES {
+        for col_frequency in 0..LOW_FREQUENCIES {
+            let mut sum = 0.0;
+            for row in 0..SIZE {
+                for col in 0..SIZE {
+                    sum += pixels[row * SIZE + col]
+                        * cosines[row_frequency * SIZE + row]
+                        * cosines[col_frequency * SIZE + col];
+                }
+            }
+            coefficients.push(sum);
+        }
+    }
+
+    let mut sorted = coefficients.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let median = (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0;
+
+    coefficients
+        .iter()
+        .fold(0u64, |hash, coefficient| (hash << 1) | (*coefficient > median) as u64)
+}
+
+/// The number of bits that differ between two hashes.
+pub fn hamming_distance(hash_a: u64, hash_b: u64) -> u32 {
+    (hash_a ^ hash_b).count_ones()
+}
+
+/// 4 x 4 Bayer matrix used for ordered dithering
+#[cfg(feature = "image")]
+const BAYER_MATRIX: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Reduce a 16 bit Rgba image to 8 bits using ordered (Bayer matrix) dithering.
+///
+/// Simply rounding each color value to 8 bits gives visible banding in smooth and dim areas.
+/// Dithering spreads the rounding error over a 4 x 4 pattern instead, so the mean value of the
+/// pattern is preserved. The result is deterministic. The alpha channel is rounded, not dithered.
+#[cfg(feature = "image")]
+pub fn dither_to_8bit(image: &ImageBuffer<Rgba<u16>, Vec<u16>>) -> RgbaImage {
+    let (width, height) = image.dimensions();
+    let mut dithered = RgbaImage::new(width, height);
+
+    for (col, row, pixel) in image.enumerate_pixels() {
+        let threshold =
+            (f64::from(BAYER_MATRIX[(row % 4) as usize][(col % 4) as usize]) + 0.5) / 16.0;
+        let mut channels = [0u8; 4];
+        for (channel, value) in channels.iter_mut().zip(pixel.data.iter()).take(3) {
+            let scaled = f64::from(*value) * 255.0 / 65535.0;
+            *channel = (scaled + threshold).floor().min(255.0) as u8;
+        }
+        channels[3] = (f64::from(pixel.data[3]) * 255.0 / 65535.0).round() as u8;
+        dithered.put_pixel(col, row, Rgba(channels));
+    }
+
+    dithered
+}
+
+/// Encode an image in the given format, returning the encoded bytes.
+///
+/// Encoding is supported for PNG, JPEG (with quality 75), GIF, BMP, ICO and PNM.
+#[cfg(feature = "image")]
+pub fn encode_image(image: &RgbaImage, format: ImageFormat) -> Result<Vec<u8>, OpenSlideError> {
+    mime_type(format)?;
+    let mut encoded = Vec::<u8>::new();
+    DynamicImage::ImageRgba8(image.clone()).write_to(&mut encoded, format)?;
+    Ok(encoded)
+}
+
+/// Encode an image as a data URI, e.g. `data:image/png;base64,...`.
+///
+/// The data URI can be used directly as the source of an image in HTML. See `encode_image()` for
+/// the supported formats.
+#[cfg(feature = "image")]
+pub fn to_data_uri(image: &RgbaImage, format: ImageFormat) -> Result<String, OpenSlideError> {
+    let encoded = encode_image(image, format)?;
+    Ok(format!("data:{};base64,{}", mime_type(format)?, base64_encode(&encoded)))
+}
+
+/// The MIME type of an image format that can be encoded.
+#[cfg(feature = "image")]
+fn mime_type(format: ImageFormat) -> Result<&'static str, OpenSlideError> {
+    match format {
+        ImageFormat::PNG => Ok("image/png"),
+        ImageFormat::JPEG => Ok("image/jpeg"),
+        ImageFormat::GIF => Ok("image/gif"),
+        ImageFormat::BMP => Ok("image/bmp"),
+        ImageFormat::ICO => Ok("image/x-icon"),
+        ImageFormat::PNM => Ok("image/x-portable-anymap"),
+        _ => Err(OpenSlideError::InvalidArgument(format!(
+            "Encoding to {:?} is not supported",
+            format
+        ))),
+    }
+}
+
+/// Encode bytes with the standard base64 alphabet, with padding.
+#[cfg(feature = "image")]
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let group = (u32::from(bytes[0]) << 16) | (u32::from(bytes[1]) << 8) | u32::from(bytes[2]);
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (group >> (18 - 6 * index)) & 0x3f;
+                encoded.push(ALPHABET[sextet as usize] as char);
+            } else {
+                encoded.push('=');
+            }
         }
+    }
+    encoded
+}
+
+/// Relative tolerance used when comparing a requested downsample factor with level downsamples.
+///
+/// Level downsamples are computed from level dimensions, so they are seldom exact powers of two
+/// (e.g. 4.0001 or 16.0003 rather than 4 and 16).
+pub const DOWNSAMPLE_TOLERANCE: f64 = 1e-3;
+
+/// Get the best level to use for displaying the given downsample factor.
+///
+/// This is the level with the largest downsample factor that is not larger than the requested
+/// one, and level 0 if the requested factor is smaller than all of them. Unlike OpenSlide, a level
+/// downsample within a relative tolerance of 1e-3 of the requested factor counts as equal, so
+/// asking for 16.0 returns a level with downsample 16.0003 instead of the level below it.
+///
+/// `level_downsamples` must be sorted in increasing order, as returned by OpenSlide.
+pub fn best_level_for_downsample(level_downsamples: &[f64], downsample_factor: f64) -> usize {
+    level_downsamples
+        .iter()
+        .rposition(|&level_downsample| {
+            level_downsample <= downsample_factor * (1.0 + DOWNSAMPLE_TOLERANCE)
+        })
+        .unwrap_or(0)
+}
 
-        *pixel = Rgba([red, green, blue, alpha]);
+/// Get the integer factor between the (width, height) dimensions of two levels, if there is one.
+///
+/// This is `Some(k)` if both dimensions of the coarse level are the dimensions of the fine level
+/// divided by `k`, rounded either down or up (as the scanners do), and `None` otherwise.
+pub fn integer_downsample(
+    fine_dimensions: (u64, u64),
+    coarse_dimensions: (u64, u64),
+) -> Option<u32> {
+    let (fine_width, fine_height) = fine_dimensions;
+    let (coarse_width, coarse_height) = coarse_dimensions;
+    if coarse_width == 0 || coarse_height == 0 {
+        return None;
     }
+    let factor = (fine_width as f64 / coarse_width as f64).round() as u64;
+    let divides =
+        |fine: u64, coarse: u64| fine / factor == coarse || fine.div_ceil(factor) == coarse;
+    if factor == 0 || !divides(fine_width, coarse_width) || !divides(fine_height, coarse_height) {
+        return None;
+    }
+    factor.to_u32()
+}
 
-    Ok(rgba_image)
+/// Map a level 0 coordinate to a coordinate at a level with the given downsample factor.
+///
+/// The coordinate is divided by the (possibly non-integer) downsample factor and rounded to the
+/// nearest integer, with halfway cases rounded away from zero.
+pub fn lvl0_to_level_coordinate(lvl0_coordinate: u64, downsample_factor: f64) -> u64 {
+    (lvl0_coordinate as f64 / downsample_factor).round() as u64
+}
+
+/// Map a coordinate at a level with the given downsample factor to a level 0 coordinate.
+///
+/// The coordinate is multiplied by the (possibly non-integer) downsample factor and rounded to the
+/// nearest integer, with halfway cases rounded away from zero.
+pub fn level_to_lvl0_coordinate(level_coordinate: u64, downsample_factor: f64) -> u64 {
+    (level_coordinate as f64 * downsample_factor).round() as u64
 }
@@ -2,13 +2,16 @@
 //!
 
 extern crate openslide;
-extern crate failure;
 extern crate float_cmp;
 extern crate image;
 
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use float_cmp::ApproxEq;
-use image::{RgbaImage};
+use image::{imageops, RgbaImage};
+use openslide::utils;
 
 fn get_slide() -> openslide::OpenSlide {
     let filename = Path::new("assets/CMU-1-Small-Region.svs");
@@ -108,3 +111,1285 @@ fn test_read_region() {
                           254, 245, 241, 255, 246, 246, 246, 255];
     assert_eq!(true_value, value.into_vec())
 }
+
+#[test]
+fn test_read_region_verified_dimensions() {
+    let filename = Path::new("assets/CMU-1-Small-Region.svs");
+    let builder = openslide::OpenSlideBuilder::new(filename).verify_region_dimensions(true);
+    let slide = match builder.open() {
+        Ok(val) => val,
+        Err(msg) => panic!("Unable to load slide:\n{}", msg),
+    };
+    let value = match slide.read_region(1510u32, 1510u32, 0u32, 3u32, 5u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!((5, 3), value.dimensions())
+}
+
+#[test]
+fn test_creating_software() {
+    let slide = get_slide();
+    let value = match slide.creating_software() {
+        Ok(val) => val,
+        Err(msg) => panic!("Creating software error:\n{}", msg),
+    };
+    assert_eq!(Some(String::from("Aperio Image Library v11.2.1")), value)
+}
+
+#[test]
+fn test_read_region_box_downsampled() {
+    let slide = get_slide();
+    let value = match slide.read_region_box_downsampled(1510u32, 1510u32, 0u32, 2u32, 2u32, 2) {
+        Ok(val) => val,
+        Err(msg) => panic!("Box downsampled read error:\n{}", msg),
+    };
+    let true_value = vec![166, 157, 190, 255, 182, 179, 197, 255,
+                          249, 247, 249, 255, 248, 249, 246, 255];
+    assert_eq!(true_value, value.into_vec())
+}
+
+#[test]
+fn test_read_region_cancelable() {
+    let slide = get_slide();
+    let cancel = AtomicBool::new(false);
+    let value = match slide.read_region_cancelable(1510u32, 1510u32, 0u32, 4u32, 4u32, &cancel) {
+        Ok(val) => val,
+        Err(msg) => panic!("Cancelable read error:\n{}", msg),
+    };
+    let true_value = match slide.read_region(1510u32, 1510u32, 0u32, 4u32, 4u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!(true_value.into_vec(), value.into_vec());
+
+    cancel.store(true, Ordering::Relaxed);
+    assert!(slide.read_region_cancelable(1510u32, 1510u32, 0u32, 4u32, 4u32, &cancel).is_err())
+}
+
+#[test]
+fn test_pixel_spacing() {
+    let slide = get_slide();
+    let value = match slide.pixel_spacing() {
+        Ok(Some(val)) => val,
+        Ok(None) => panic!("Pixel spacing is missing"),
+        Err(msg) => panic!("Pixel spacing error:\n{}", msg),
+    };
+    assert!(value.0.approx_eq(&0.499, f64::EPSILON, 2));
+    assert!(value.1.approx_eq(&0.499, f64::EPSILON, 2))
+}
+
+#[test]
+fn test_physical_dimensions_mm() {
+    let slide = get_slide();
+    let value = match slide.physical_dimensions_mm() {
+        Ok(Some(val)) => val,
+        Ok(None) => panic!("Physical dimensions are missing"),
+        Err(msg) => panic!("Physical dimensions error:\n{}", msg),
+    };
+    assert!((value.0 - 1.10778).abs() < 1e-9);
+    assert!((value.1 - 1.480533).abs() < 1e-9)
+}
+
+#[test]
+fn test_macro_tissue_only() {
+    let slide = get_slide();
+    let value = match slide.macro_tissue_only() {
+        Ok(Some(val)) => val,
+        Ok(None) => panic!("Macro image is missing"),
+        Err(msg) => panic!("Macro tissue error:\n{}", msg),
+    };
+    assert_eq!((854, 431), value.dimensions())
+}
+
+#[test]
+fn test_plan_tiling() {
+    let slide = get_slide();
+    let value = match slide.plan_tiling(10, 256) {
+        Ok(val) => val,
+        Err(msg) => panic!("Tiling plan error:\n{}", msg),
+    };
+    assert_eq!(0, value.level);
+    assert_eq!(256, value.tile_size);
+    assert!(value.foreground_fraction > 0.0 && value.foreground_fraction <= 1.0);
+    assert!(value.estimated_tile_count >= 10)
+}
+
+#[test]
+fn test_level_compression_info() {
+    let slide = get_slide();
+    let value = match slide.level_compression_info(0) {
+        Ok(Some(val)) => val,
+        Ok(None) => panic!("Compression info is missing"),
+        Err(msg) => panic!("Compression info error:\n{}", msg),
+    };
+    assert_eq!("JPEG", value.compression);
+    assert_eq!(Some(30), value.quality)
+}
+
+#[test]
+fn test_open_dir() {
+    let mut paths = Vec::new();
+    for entry in openslide::open_dir(Path::new("assets")) {
+        match entry {
+            Ok((path, slide)) => {
+                assert!(slide.get_level_count().is_ok());
+                paths.push(path);
+            }
+            Err((path, msg)) => panic!("Open dir error for {}:\n{}", path.display(), msg),
+        }
+    }
+    assert_eq!(vec![Path::new("assets/CMU-1-Small-Region.svs").to_path_buf()], paths)
+}
+
+#[test]
+fn test_open_dir_missing() {
+    let path = Path::new("assets/missing");
+    let entries: Vec<_> = openslide::open_dir(path).collect();
+    assert_eq!(1, entries.len());
+    match entries[0] {
+        Err((ref err_path, openslide::OpenSlideError::Io(_))) => assert_eq!(path, err_path),
+        _ => panic!("Expected an io error for {}", path.display()),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_open_dir_non_utf8_name() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = std::env::temp_dir().join("openslide_test_open_dir_non_utf8");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(OsStr::from_bytes(b"slide-\xff.svs"));
+    std::fs::write(&path, b"").unwrap();
+    let entries: Vec<_> = openslide::open_dir(&dir).collect();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(1, entries.len());
+    match entries[0] {
+        Err((ref err_path, openslide::OpenSlideError::InvalidPath(_))) => {
+            assert_eq!(&path, err_path)
+        }
+        _ => panic!("Expected an invalid path error for {}", path.display()),
+    }
+}
+
+#[test]
+fn test_overview_phash() {
+    let slide = get_slide();
+    let value = match slide.overview_phash() {
+        Ok(val) => val,
+        Err(msg) => panic!("Overview hash error:\n{}", msg),
+    };
+    assert_eq!(value, get_slide().overview_phash().unwrap())
+}
+
+#[test]
+fn test_property_count() {
+    let slide = get_slide();
+    let value = match slide.property_count() {
+        Ok(val) => val,
+        Err(msg) => panic!("Property count error:\n{}", msg),
+    };
+    assert_eq!(slide.get_properties().unwrap().len(), value)
+}
+
+#[test]
+fn test_parsed_properties() {
+    let slide = get_slide();
+    let properties = match slide.parsed_properties() {
+        Ok(val) => val,
+        Err(msg) => panic!("Parsed properties error:\n{}", msg),
+    };
+    assert_eq!(properties.vendor(), Some(String::from("aperio")));
+    assert_eq!(properties.aperio().and_then(|aperio| aperio.app_mag), Some(20));
+    assert!(properties.hamamatsu().is_none());
+    assert!(properties.leica().is_none());
+    assert!(properties.philips().is_none());
+}
+
+#[test]
+fn test_read_max_detail_around() {
+    let slide = get_slide();
+    let region = match slide.read_max_detail_around((10, 2218), (10, 10), 100) {
+        Ok(val) => val,
+        Err(msg) => panic!("Max detail read error:\n{}", msg),
+    };
+    assert_eq!(0, region.level);
+    assert_eq!((5, 2210, 10, 10), region.level0_rect);
+    assert_eq!((10, 10), region.image.dimensions());
+
+    // The slide has a single level, at which a 20 x 20 window does not fit in 100 pixels
+    assert!(slide
+        .read_max_detail_around((10, 2218), (20, 20), 100)
+        .is_err());
+}
+
+#[test]
+fn test_builder_word_representation() {
+    let filename = Path::new("assets/CMU-1-Small-Region.svs");
+    let builder = openslide::OpenSlideBuilder::new(filename)
+        .word_representation(utils::WordRepresentation::BigEndian);
+    let slide = match builder.open() {
+        Ok(val) => val,
+        Err(msg) => panic!("Unable to load slide:\n{}", msg),
+    };
+    let value = match slide.read_region(1510u32, 1510u32, 0u32, 4u32, 4u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    let true_value = match get_slide().read_region(1510u32, 1510u32, 0u32, 4u32, 4u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!(true_value.into_vec(), value.into_vec())
+}
+
+#[test]
+fn test_stitch_regions() {
+    let slide = get_slide();
+    let region = |row, col| openslide::Region {
+        top_left_lvl0_row: row,
+        top_left_lvl0_col: col,
+        level: 0,
+        height: 4,
+        width: 4,
+    };
+    let regions = [region(1510, 1510), region(100, 200), region(2000, 300)];
+    let placements = [(0, 0), (2, 2), (5, 5)];
+    let canvas = match slide.stitch_regions(&regions, (6, 8), &placements) {
+        Ok(val) => val,
+        Err(msg) => panic!("Stitch regions error:\n{}", msg),
+    };
+    let first = match slide.read_region(1510u32, 1510u32, 0u32, 4u32, 4u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    let second = match slide.read_region(100u32, 200u32, 0u32, 4u32, 4u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    let third = match slide.read_region(2000u32, 300u32, 0u32, 4u32, 4u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!((8, 6), canvas.dimensions());
+    assert_eq!(first.get_pixel(1, 1), canvas.get_pixel(1, 1));
+    assert_eq!(second.get_pixel(0, 0), canvas.get_pixel(2, 2)); // Last write wins
+    assert_eq!(third.get_pixel(0, 0), canvas.get_pixel(5, 5));
+    assert_eq!(0, canvas.get_pixel(7, 0).data[3]);
+    assert!(slide.stitch_regions(&regions, (6, 8), &placements[..2]).is_err())
+}
+
+#[test]
+fn test_reopen() {
+    let slide = get_slide();
+    let reopened = match slide.reopen() {
+        Ok(val) => val,
+        Err(msg) => panic!("Unable to reopen slide:\n{}", msg),
+    };
+    drop(slide);
+    assert_eq!(Path::new("assets/CMU-1-Small-Region.svs"), reopened.path());
+    match reopened.get_level0_dimensions() {
+        Ok(dimensions) => assert_eq!((2220, 2967), dimensions),
+        Err(msg) => panic!("Level 0 dimensions error:\n{}", msg),
+    }
+}
+
+#[test]
+fn test_is_region_blank() {
+    let slide = get_slide();
+    match slide.is_region_blank((1000, 1000), (240, 240), 0, 0.0) {
+        Ok(blank) => assert!(!blank),
+        Err(msg) => panic!("Blank region error:\n{}", msg),
+    }
+    match slide.is_region_blank((1000, 1000), (240, 240), 0, 1.01) {
+        Ok(blank) => assert!(blank),
+        Err(msg) => panic!("Blank region error:\n{}", msg),
+    }
+    assert!(slide.is_region_blank((1000, 1000), (0, 240), 0, 0.5).is_err())
+}
+
+#[test]
+fn test_read_region_gray() {
+    let slide = get_slide();
+    let transparent = utils::TransparentGray::Background;
+    let value = match slide.read_region_gray(1510u32, 1510u32, 0u32, 4u32, 6u32, transparent) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    let region = match slide.read_region(1510u32, 1510u32, 0u32, 4u32, 6u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    let true_value = utils::rgba_to_gray(&region, image::Rgb([255, 255, 255]), transparent);
+    assert_eq!((6, 4), value.dimensions());
+    assert_eq!(true_value.into_vec(), value.into_vec())
+}
+
+#[test]
+fn test_fingerprint() {
+    let fingerprint = match get_slide().fingerprint() {
+        Ok(val) => val,
+        Err(msg) => panic!("Fingerprint error:\n{}", msg),
+    };
+    let other_fingerprint = match get_slide().fingerprint() {
+        Ok(val) => val,
+        Err(msg) => panic!("Fingerprint error:\n{}", msg),
+    };
+    assert_eq!(fingerprint, other_fingerprint);
+    assert!(fingerprint.quickhash_1.is_some());
+    assert_eq!((2220, 2967), fingerprint.level0_dimensions);
+    assert_eq!(Some("aperio".to_string()), fingerprint.vendor);
+    assert!(fingerprint.to_string().contains(" 2220x2967 aperio 0.499x0.499mpp"));
+
+    let mut fingerprints = HashSet::new();
+    fingerprints.insert(fingerprint);
+    assert!(!fingerprints.insert(other_fingerprint))
+}
+
+#[test]
+fn test_read_region_half_and_quarter() {
+    let slide = get_slide();
+    let region = match slide.read_region(1510u32, 1510u32, 0u32, 8u32, 12u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+
+    let half = match slide.read_region_half((1510, 1510), (9, 12)) {
+        Ok(val) => val,
+        Err(msg) => panic!("Half resolution read error:\n{}", msg),
+    };
+    let true_half = match utils::box_downsample(&region, 2) {
+        Ok(val) => val,
+        Err(msg) => panic!("Box downsample error:\n{}", msg),
+    };
+    assert_eq!((6, 4), half.dimensions());
+    assert_eq!(true_half.into_vec(), half.into_vec());
+
+    let quarter = match slide.read_region_quarter((1510, 1510), (8, 12)) {
+        Ok(val) => val,
+        Err(msg) => panic!("Quarter resolution read error:\n{}", msg),
+    };
+    let true_quarter = match utils::box_downsample(&region, 4) {
+        Ok(val) => val,
+        Err(msg) => panic!("Box downsample error:\n{}", msg),
+    };
+    assert_eq!((3, 2), quarter.dimensions());
+    assert_eq!(true_quarter.into_vec(), quarter.into_vec())
+}
+
+#[test]
+fn test_scan_resolution_tier() {
+    match get_slide().scan_resolution_tier() {
+        Ok(tier) => assert_eq!(openslide::ScanTier::X20, tier),
+        Err(msg) => panic!("Scan resolution tier error:\n{}", msg),
+    }
+    assert_eq!(openslide::ScanTier::X10, openslide::ScanTier::from_magnification(10.5));
+    assert_eq!(openslide::ScanTier::X40, openslide::ScanTier::from_magnification(37.0));
+    assert_eq!(openslide::ScanTier::Other(60.0), openslide::ScanTier::from_magnification(60.0))
+}
+
+#[test]
+fn test_conversion_error_names_argument() {
+    match get_slide().get_level_dimensions(u64::MAX) {
+        Ok(_) => panic!("Expected a conversion error"),
+        Err(msg) => assert!(msg.to_string().contains("`level`")),
+    }
+}
+
+#[test]
+fn test_thumbnail_or_render() {
+    let thumbnail = match get_slide().thumbnail_or_render(100) {
+        Ok(val) => val,
+        Err(msg) => panic!("Thumbnail error:\n{}", msg),
+    };
+    // The embedded thumbnail is 574 x 768
+    assert_eq!((75, 100), thumbnail.dimensions())
+}
+
+#[test]
+fn test_read_region_clipped() {
+    let slide = get_slide();
+    let region = match slide.read_region_clipped(2960u32, 2200u32, 0u32, 10u32, 10u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!(0, region.level);
+    assert_eq!((2960, 2200, 7, 10), region.level0_rect);
+    assert_eq!((10, 7), region.image.dimensions())
+}
+
+#[test]
+fn test_associated_image_properties() {
+    let slide = get_slide();
+    let properties = match slide.associated_image_properties("label") {
+        Ok(val) => val,
+        Err(msg) => panic!("Associated image properties error:\n{}", msg),
+    };
+    assert_eq!(Some(&"387".to_string()), properties.get("openslide.associated.label.width"));
+    assert_eq!(Some(&"463".to_string()), properties.get("openslide.associated.label.height"));
+    assert!(properties.keys().all(|key| key.starts_with("openslide.associated.label.")));
+    assert!(slide.associated_image_properties("nonexisting").is_err())
+}
+
+#[test]
+fn test_read_region_detail() {
+    let slide = get_slide();
+    let value = match slide.read_region_detail((1510, 1510), (4, 6), 0, 0) {
+        Ok(val) => val,
+        Err(msg) => panic!("Detail read error:\n{}", msg),
+    };
+    // A level has no detail over itself
+    assert_eq!((6, 4), value.dimensions());
+    assert!(value.pixels().all(|pixel| pixel.data[..3] == [128, 128, 128]))
+}
+
+#[test]
+fn test_export_legacy_pyramid() {
+    let out_dir = std::env::temp_dir().join("openslide_test_export_legacy_pyramid");
+    if let Err(msg) = get_slide().export_legacy_pyramid(&out_dir, 500) {
+        panic!("Export legacy pyramid error:\n{}", msg);
+    }
+    let smallest = match image::open(out_dir.join("0.jpg")) {
+        Ok(val) => val,
+        Err(msg) => panic!("Unable to open exported image:\n{}", msg),
+    };
+    assert_eq!((555, 742), image::GenericImageView::dimensions(&smallest));
+    assert!(out_dir.join("2.jpg").exists());
+    assert!(!out_dir.join("3.jpg").exists());
+    let _ = std::fs::remove_dir_all(&out_dir);
+}
+
+#[test]
+fn test_physical_origin_microns() {
+    // The slide has no bounds, so this is the Aperio offset (Left = 25.691574, Top = 23.449873)
+    match get_slide().physical_origin_microns() {
+        Ok(Some((x, y))) => {
+            assert!(x.approx_eq(&25_691.574, 1e-6, 2));
+            assert!(y.approx_eq(&23_449.873, 1e-6, 2));
+        }
+        Ok(None) => panic!("Expected a physical origin"),
+        Err(msg) => panic!("Physical origin error:\n{}", msg),
+    }
+}
+
+#[test]
+fn test_read_region_trilinear() {
+    let slide = get_slide();
+    // With a single level, this is the level 0 region resized to the output size
+    let value = match slide.read_region_trilinear((1500, 1500), (5, 10), 2.0) {
+        Ok(val) => val,
+        Err(msg) => panic!("Trilinear read error:\n{}", msg),
+    };
+    assert_eq!((10, 5), value.dimensions());
+    let region = match slide.read_region(1500u32, 1500u32, 0u32, 10u32, 20u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    let resized = image::imageops::resize(&region, 10, 5, image::FilterType::Triangle);
+    assert_eq!(resized.into_vec(), value.into_vec());
+    assert!(slide.read_region_trilinear((1500, 1500), (5, 10), 0.0).is_err())
+}
+
+#[test]
+fn test_is_calibrated() {
+    match get_slide().is_calibrated() {
+        Ok(calibrated) => assert!(calibrated),
+        Err(msg) => panic!("Calibration error:\n{}", msg),
+    }
+}
+
+#[test]
+fn test_associated_images_cached() {
+    let slide = get_slide();
+    let first = match slide.get_associated_images() {
+        Ok(val) => val,
+        Err(msg) => panic!("Associated images error:\n{}", msg),
+    };
+    let second = match slide.get_associated_images() {
+        Ok(val) => val,
+        Err(msg) => panic!("Associated images error:\n{}", msg),
+    };
+    assert_eq!(first.len(), second.len());
+    for (name, image) in first {
+        assert_eq!(image.into_vec(), second[&name].clone().into_vec());
+    }
+}
+
+#[test]
+fn test_read_region_f32() {
+    let slide = get_slide();
+    let value = match slide.read_region_f32(1510u32, 1510u32, 0u32, 4u32, 6u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!((6, 4), value.dimensions());
+    assert!(value.pixels().all(|pixel| pixel.data.iter().all(|v| *v >= 0.0 && *v <= 1.0)))
+}
+
+#[test]
+fn test_scanned_fraction() {
+    // The slide has no bounds
+    match get_slide().scanned_fraction() {
+        Ok(fraction) => assert!(fraction.approx_eq(&1.0, f64::EPSILON, 2)),
+        Err(msg) => panic!("Scanned fraction error:\n{}", msg),
+    }
+}
+
+#[test]
+fn test_read_roi_tiled() {
+    let slide = get_slide();
+    let tiles = match slide.read_roi_tiled((1500, 1400, 25, 30), 16) {
+        Ok(tiles) => tiles.collect::<Result<Vec<_>, _>>(),
+        Err(msg) => panic!("Tiled read error:\n{}", msg),
+    };
+    let tiles = match tiles {
+        Ok(val) => val,
+        Err(msg) => panic!("Tile read error:\n{}", msg),
+    };
+    let positions: Vec<(u32, u32)> = tiles.iter().map(|(row, col, _)| (*row, *col)).collect();
+    assert_eq!(vec![(0, 0), (0, 1), (1, 0), (1, 1)], positions);
+    assert_eq!((16, 16), tiles[0].2.dimensions());
+    assert_eq!((14, 9), tiles[3].2.dimensions());
+
+    let corner = match slide.read_region(1516u32, 1416u32, 0u32, 9u32, 14u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!(corner.into_vec(), tiles[3].2.clone().into_vec());
+    assert!(slide.read_roi_tiled((2960, 0, 10, 10), 16).is_err())
+}
+
+#[test]
+fn test_level_coordinate_conversion() {
+    let slide = get_slide();
+    let level_coordinates = match slide.level0_to_level(0, (1234, 2345)) {
+        Ok(val) => val,
+        Err(msg) => panic!("Level 0 to level error:\n{}", msg),
+    };
+    assert_eq!((1234, 2345), level_coordinates);
+    let lvl0_coordinates = match slide.level_to_level0(0, level_coordinates) {
+        Ok(val) => val,
+        Err(msg) => panic!("Level to level 0 error:\n{}", msg),
+    };
+    assert_eq!((1234, 2345), lvl0_coordinates);
+    for &corner in &[(0, 0), (2219, 2966)] {
+        let round_trip = slide
+            .level0_to_level(0, corner)
+            .and_then(|coordinates| slide.level_to_level0(0, coordinates));
+        assert_eq!(corner, round_trip.unwrap());
+    }
+    assert!(slide.level0_to_level(1, (0, 0)).is_err());
+    assert!(slide.level_to_level0(1, (0, 0)).is_err());
+}
+
+#[test]
+fn test_tiles() {
+    let slide = get_slide();
+    let tiles = match slide.tiles(0, (1000, 1000)) {
+        Ok(tiles) => {
+            assert_eq!(9, tiles.len());
+            tiles.collect::<Result<Vec<_>, _>>()
+        }
+        Err(msg) => panic!("Tiles error:\n{}", msg),
+    };
+    let tiles = match tiles {
+        Ok(val) => val,
+        Err(msg) => panic!("Tile read error:\n{}", msg),
+    };
+    let positions: Vec<(u32, u32)> = tiles.iter().map(|(row, col, _)| (*row, *col)).collect();
+    assert_eq!((0, 0), positions[0]);
+    assert_eq!((0, 2), positions[2]);
+    assert_eq!((2, 2), positions[8]);
+    assert_eq!((1000, 1000), tiles[0].2.dimensions());
+    assert_eq!((220, 1000), tiles[2].2.dimensions());
+    assert_eq!((220, 967), tiles[8].2.dimensions());
+
+    let corner = match slide.read_region(2000u32, 2000u32, 0u32, 967u32, 220u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!(corner.into_vec(), tiles[8].2.clone().into_vec());
+    assert!(slide.tiles(0, (0, 16)).is_err());
+}
+
+#[test]
+fn test_deep_zoom_generator() {
+    let slide = get_slide();
+    let generator = match openslide::DeepZoomGenerator::new(&slide, 254, 1, false) {
+        Ok(val) => val,
+        Err(msg) => panic!("Deep Zoom generator error:\n{}", msg),
+    };
+    assert_eq!(13, generator.level_count());
+    assert_eq!((2220, 2967), generator.level_dimensions(12).unwrap());
+    assert_eq!((1110, 1484), generator.level_dimensions(11).unwrap());
+    assert_eq!((1, 1), generator.level_dimensions(0).unwrap());
+    assert_eq!((9, 12), generator.level_tiles(12).unwrap());
+    assert_eq!((5, 6), generator.level_tiles(11).unwrap());
+    assert!(generator.level_dimensions(13).is_err());
+
+    let tile = match generator.get_tile(12, (1, 0)) {
+        Ok(val) => val,
+        Err(msg) => panic!("Deep Zoom tile error:\n{}", msg),
+    };
+    assert_eq!((256, 255), tile.dimensions());
+    let region = match slide.read_region(0u32, 253u32, 0u32, 255u32, 256u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!(region.into_vec(), tile.into_vec());
+
+    let corner = match generator.get_tile(12, (8, 11)) {
+        Ok(val) => val,
+        Err(msg) => panic!("Deep Zoom tile error:\n{}", msg),
+    };
+    assert_eq!((189, 174), corner.dimensions());
+    let downsampled = match generator.get_tile(11, (0, 0)) {
+        Ok(val) => val,
+        Err(msg) => panic!("Deep Zoom tile error:\n{}", msg),
+    };
+    assert_eq!((255, 255), downsampled.dimensions());
+
+    assert!(generator.get_tile(12, (9, 0)).is_err());
+    assert!(generator.get_tile(13, (0, 0)).is_err());
+    assert!(openslide::DeepZoomGenerator::new(&slide, 0, 0, false).is_err());
+}
+
+#[test]
+fn test_export_level() {
+    let out_dir = std::env::temp_dir().join("openslide_test_export_level");
+    let num_tiles = match get_slide().export_level(0, 1024, &out_dir, true) {
+        Ok(val) => val,
+        Err(msg) => panic!("Export level error:\n{}", msg),
+    };
+    assert_eq!(9, num_tiles);
+    let manifest = match std::fs::read_to_string(out_dir.join("manifest.csv")) {
+        Ok(val) => val,
+        Err(msg) => panic!("Unable to read manifest:\n{}", msg),
+    };
+    let lines: Vec<&str> = manifest.lines().collect();
+    assert_eq!(10, lines.len());
+    assert_eq!("filename,level,x_lvl0,y_lvl0,width,height,mpp", lines[0]);
+    assert_eq!("0_2_2.png,0,2048,2048,172,919,0.499", lines[9]);
+    assert!(out_dir.join("0_2_2.png").exists());
+    let _ = std::fs::remove_dir_all(&out_dir);
+}
+
+#[test]
+fn test_read_region_rgb() {
+    let slide = get_slide();
+    let value = match slide.read_region_rgb(1000u32, 1000u32, 0u32, 10u32, 20u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Rgb read error:\n{}", msg),
+    };
+    assert_eq!((20, 10), value.dimensions());
+    // The slide is opaque here, so the colors are those of read_region
+    let region = slide.read_region(1000u32, 1000u32, 0u32, 10u32, 20u32).unwrap();
+    for (rgb, rgba) in value.pixels().zip(region.pixels()) {
+        assert_eq!(255, rgba.data[3]);
+        assert_eq!(rgb.data, [rgba.data[0], rgba.data[1], rgba.data[2]]);
+    }
+}
+
+#[test]
+fn test_read_region_stains() {
+    let slide = get_slide();
+    let stain_matrix = utils::HE_STAIN_MATRIX;
+    let stains = match slide.read_region_stains(1510u32, 1510u32, 0u32, 4u32, 6u32, stain_matrix) {
+        Ok(val) => val,
+        Err(msg) => panic!("Stain read error:\n{}", msg),
+    };
+    for stain in stains.iter() {
+        assert_eq!((6, 4), stain.dimensions());
+    }
+}
+
+#[test]
+fn test_read_region_aligned() {
+    let slide = get_slide();
+    let value = match slide.read_region_aligned((1000, 1000), (70, 100), 0, 32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Aligned read error:\n{}", msg),
+    };
+    assert_eq!((96, 64), value.dimensions());
+    // Clipped to (7, 100) before aligning
+    assert!(slide.read_region_aligned((2960, 1000), (70, 100), 0, 32).is_err());
+    assert!(slide.read_region_aligned((1000, 1000), (70, 100), 0, 0).is_err())
+}
+
+#[test]
+fn test_read_region_words() {
+    let slide = get_slide();
+    let (words, width, height) = match slide.read_region_words(1510u32, 1510u32, 0u32, 4u32, 6u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!((6, 4, 24), (width, height, words.len()));
+    let word_repr = utils::WordRepresentation::Native;
+    let decoded = match utils::decode_buffer(&words, height, width, word_repr) {
+        Ok(val) => val,
+        Err(msg) => panic!("Decode buffer error:\n{}", msg),
+    };
+    let true_value = match slide.read_region(1510u32, 1510u32, 0u32, 4u32, 6u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!(true_value.into_vec(), decoded.into_vec())
+}
+
+#[test]
+fn test_clone_shares_handle() {
+    let slide = get_slide();
+    let clones: Vec<_> = (0..4).map(|_| slide.clone()).collect();
+    let survivor = clones[2].clone();
+    drop(clones);
+    drop(slide);
+    let region = match survivor.read_region(0u32, 0u32, 0u32, 2u32, 2u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!((2, 2), region.dimensions());
+    assert_eq!(2220, survivor.get_level0_dimensions().unwrap().0);
+}
+
+#[test]
+fn test_center_crop() {
+    let slide = get_slide();
+    let value = match slide.center_crop((100, 200), 0) {
+        Ok(val) => val,
+        Err(msg) => panic!("Center crop error:\n{}", msg),
+    };
+    assert_eq!((200, 100), value.dimensions());
+    let true_value = match slide.read_region(1433u32, 1010u32, 0u32, 100u32, 200u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!(true_value.into_vec(), value.into_vec());
+    // Clamped to the slide
+    let value = match slide.center_crop((5000, 10), 0) {
+        Ok(val) => val,
+        Err(msg) => panic!("Center crop error:\n{}", msg),
+    };
+    assert_eq!((10, 2967), value.dimensions())
+}
+
+#[test]
+fn test_read_associated_image() {
+    let slide = get_slide();
+    let value = match slide.read_associated_image("label") {
+        Ok(val) => val,
+        Err(msg) => panic!("Read associated image error:\n{}", msg),
+    };
+    assert_eq!((387, 463), value.dimensions());
+    assert!(slide.read_associated_image("nonexisting").is_err())
+}
+
+#[test]
+fn test_read_associated_image_original() {
+    let slide = get_slide();
+    for name in &["label", "macro", "thumbnail"] {
+        match slide.read_associated_image_original(name) {
+            Ok(val) => assert!(val.is_none()),
+            Err(msg) => panic!("Read original associated image error:\n{}", msg),
+        }
+    }
+    assert!(slide.read_associated_image_original("nonexisting").is_err())
+}
+
+#[test]
+fn test_associated_image_names() {
+    let slide = get_slide();
+    let value = match slide.get_associated_image_names() {
+        Ok(val) => val,
+        Err(msg) => panic!("Get associated image names error:\n{}", msg),
+    };
+    let true_value: HashSet<String> =
+        ["label", "macro", "thumbnail"].iter().map(|s| s.to_string()).collect();
+    assert_eq!(true_value, value.into_iter().collect::<HashSet<_>>())
+}
+
+#[test]
+fn test_embedded_annotations() {
+    let slide = get_slide();
+    let value = match slide.embedded_annotations() {
+        Ok(val) => val,
+        Err(msg) => panic!("Embedded annotations error:\n{}", msg),
+    };
+    assert!(value.is_empty())
+}
+
+#[test]
+fn test_associated_image_dimensions() {
+    let slide = get_slide();
+    let value = match slide.get_associated_image_dimensions("macro") {
+        Ok(val) => val,
+        Err(msg) => panic!("Get associated image dimensions error:\n{}", msg),
+    };
+    assert_eq!((1280, 431), value);
+    assert!(slide.get_associated_image_dimensions("nonexisting").is_err())
+}
+
+#[test]
+fn test_global_histogram() {
+    let slide = get_slide();
+    let value = match slide.global_histogram(0) {
+        Ok(val) => val,
+        Err(msg) => panic!("Global histogram error:\n{}", msg),
+    };
+    let transparent = utils::TransparentGray::Background;
+    let level = match slide.read_region_gray(0u32, 0u32, 0u32, 2967u32, 2220u32, transparent) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    let mut true_value = [0u64; 256];
+    for pixel in level.pixels() {
+        true_value[pixel.data[0] as usize] += 1;
+    }
+    assert_eq!(2220 * 2967, value.iter().sum::<u64>());
+    assert_eq!(true_value.to_vec(), value.to_vec())
+}
+
+#[test]
+fn test_detect_vendor() {
+    let filename = Path::new("assets/CMU-1-Small-Region.svs");
+    let value = match openslide::OpenSlide::detect_vendor(filename) {
+        Ok(val) => val,
+        Err(msg) => panic!("Detect vendor error:\n{}", msg),
+    };
+    assert_eq!("aperio", value);
+    assert!(openslide::OpenSlide::detect_vendor(Path::new("assets/nonexisting.svs")).is_err());
+    assert!(openslide::OpenSlide::detect_vendor(Path::new("Cargo.toml")).is_err())
+}
+
+#[test]
+fn test_integer_downsample_between() {
+    let slide = get_slide();
+    let value = match slide.integer_downsample_between(0, 0) {
+        Ok(val) => val,
+        Err(msg) => panic!("Integer downsample error:\n{}", msg),
+    };
+    assert_eq!(Some(1), value);
+    assert!(slide.integer_downsample_between(0, 1).is_err())
+}
+
+#[test]
+fn test_get_mpp() {
+    let slide = get_slide();
+    let (mpp_x, mpp_y) = match slide.get_mpp() {
+        Ok(val) => val,
+        Err(msg) => panic!("Get mpp error:\n{}", msg),
+    };
+    assert!(mpp_x.approx_eq(&0.499, f64::EPSILON, 2));
+    assert!(mpp_y.approx_eq(&0.499, f64::EPSILON, 2))
+}
+
+#[test]
+fn test_tile_read_callback() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&events);
+    let filename = Path::new("assets/CMU-1-Small-Region.svs");
+    let slide = match openslide::OpenSlideBuilder::new(filename)
+        .tile_read_callback(Box::new(move |event| sink.lock().unwrap().push(event)))
+        .open()
+    {
+        Ok(val) => val,
+        Err(msg) => panic!("Open slide error:\n{}", msg),
+    };
+    if let Err(msg) = slide.read_region(10u32, 20u32, 0u32, 30u32, 40u32) {
+        panic!("Read region error:\n{}", msg);
+    }
+    let events = events.lock().unwrap();
+    assert_eq!(1, events.len());
+    assert_eq!((0, (10, 20), (30, 40)), (events[0].level, events[0].top_left_lvl0, events[0].size))
+}
+
+#[test]
+fn test_get_objective_power() {
+    let slide = get_slide();
+    let value = match slide.get_objective_power() {
+        Ok(val) => val,
+        Err(msg) => panic!("Get objective power error:\n{}", msg),
+    };
+    assert_eq!(20, value)
+}
+
+#[test]
+fn test_read_region_gamma() {
+    let slide = get_slide();
+    let value = match slide.read_region_gamma(1000u32, 1000u32, 0u32, 10u32, 20u32, 1.0) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    let true_value = match slide.read_region(1000u32, 1000u32, 0u32, 10u32, 20u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!(true_value.into_vec(), value.into_vec());
+    assert!(slide.read_region_gamma(1000u32, 1000u32, 0u32, 10u32, 20u32, -1.0).is_err())
+}
+
+#[test]
+fn test_get_bounds() {
+    let slide = get_slide();
+    let value = match slide.get_bounds() {
+        Ok(val) => val,
+        Err(msg) => panic!("Get bounds error:\n{}", msg),
+    };
+    assert_eq!(None, value)
+}
+
+#[test]
+fn test_level_for_screen() {
+    let slide = get_slide();
+    for scale in [0.5, 1.0, 4.0].iter() {
+        match slide.level_for_screen(*scale) {
+            Ok(val) => assert_eq!(0, val),
+            Err(msg) => panic!("Level for screen error:\n{}", msg),
+        }
+    }
+    assert!(slide.level_for_screen(0.0).is_err());
+    assert!(slide.level_for_screen(f64::INFINITY).is_err())
+}
+
+#[test]
+fn test_thumbnail() {
+    let slide = get_slide();
+    let value = match slide.thumbnail(100, 300) {
+        Ok(val) => val,
+        Err(msg) => panic!("Thumbnail error:\n{}", msg),
+    };
+    // Level 0 (2220 x 2967) is the only level, and is shrunk to fit the width
+    assert_eq!((100, 134), value.dimensions());
+    assert!(slide.thumbnail(0, 100).is_err())
+}
+
+#[test]
+fn test_mpp_all_levels() {
+    let slide = get_slide();
+    let value = match slide.mpp_all_levels() {
+        Ok(val) => val,
+        Err(msg) => panic!("Mpp all levels error:\n{}", msg),
+    };
+    assert_eq!(1, value.len());
+    let (mpp_x, mpp_y) = value[0].expect("Slide has a pixel spacing");
+    assert!(mpp_x.approx_eq(&0.499, f64::EPSILON, 2));
+    assert!(mpp_y.approx_eq(&0.499, f64::EPSILON, 2))
+}
+
+#[test]
+fn test_read_region_luma() {
+    let slide = get_slide();
+    let value = match slide.read_region_luma(1000u32, 1000u32, 0u32, 10u32, 20u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!((20, 10), value.dimensions());
+    let region = match slide.read_region(1000u32, 1000u32, 0u32, 10u32, 20u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    for (luma, pixel) in value.pixels().zip(region.pixels()) {
+        let [red, green, blue, _] = pixel.data;
+        let true_value =
+            0.299 * f64::from(red) + 0.587 * f64::from(green) + 0.114 * f64::from(blue);
+        assert!((f64::from(luma.data[0]) - true_value).abs() <= 0.5 + 1e-6);
+    }
+}
+
+#[test]
+fn test_library_version() {
+    let value = match openslide::OpenSlide::library_version() {
+        Ok(val) => val,
+        Err(msg) => panic!("Library version error:\n{}", msg),
+    };
+    assert!(value.starts_with(|c: char| c.is_ascii_digit()), "{}", value)
+}
+
+#[test]
+fn test_level0_dimensions_match_level_dimensions() {
+    let slide = get_slide();
+    let level0_dimensions = match slide.get_level0_dimensions() {
+        Ok(val) => val,
+        Err(msg) => panic!("Level 0 dimension error:\n{}", msg),
+    };
+    let level_dimensions = match slide.get_level_dimensions(0u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Level dimension error:\n{}", msg),
+    };
+    assert_eq!(level0_dimensions, level_dimensions)
+}
+
+#[test]
+fn test_read_region_raw() {
+    let slide = get_slide();
+    let value = match slide.read_region_raw(1510u32, 1510u32, 0u32, 4u32, 6u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!(24, value.len());
+    // The region is opaque
+    assert!(value.iter().all(|word| word >> 24 == 0xff));
+    let (words, _, _) = slide.read_region_words(1510u32, 1510u32, 0u32, 4u32, 6u32).unwrap();
+    assert_eq!(words, value)
+}
+
+#[test]
+fn test_read_region_checked() {
+    let slide = get_slide();
+    let value = match slide.read_region_checked(2957u32, 2210u32, 0u32, 10u32, 10u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!((10, 10), value.dimensions());
+    // Partially and entirely outside of the slide
+    assert!(slide.read_region_checked(2958u32, 2210u32, 0u32, 10u32, 10u32).is_err());
+    assert!(slide.read_region_checked(2957u32, 2211u32, 0u32, 10u32, 10u32).is_err());
+    assert!(slide.read_region_checked(3000u32, 3000u32, 0u32, 10u32, 10u32).is_err())
+}
+
+#[test]
+fn test_native_orientation() {
+    let slide = get_slide();
+    match slide.native_orientation() {
+        Ok(val) => assert_eq!(utils::Orientation::Normal, val),
+        Err(msg) => panic!("Native orientation error:\n{}", msg),
+    }
+
+    let filename = Path::new("assets/CMU-1-Small-Region.svs");
+    let oriented = match openslide::OpenSlideBuilder::new(filename).auto_orient(true).open() {
+        Ok(val) => val,
+        Err(msg) => panic!("Open slide error:\n{}", msg),
+    };
+    let value = oriented.read_region(100u32, 200u32, 0u32, 10u32, 20u32).unwrap();
+    let true_value = slide.read_region(100u32, 200u32, 0u32, 10u32, 20u32).unwrap();
+    assert_eq!(true_value.into_vec(), value.into_vec())
+}
+
+#[test]
+fn test_read_region_signed() {
+    let slide = get_slide();
+    let value = match slide.read_region_signed((10, -5), 0, (4, 10)) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!((10, 4), value.dimensions());
+    // The overhang is transparent, and the rest is the slide
+    assert!((0..4).all(|row| (0..5).all(|col| value.get_pixel(col, row).data[3] == 0)));
+    let true_value = match slide.read_region(10u32, 0u32, 0u32, 4u32, 5u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    let inside = imageops::crop(&mut value.clone(), 5, 0, 5, 4).to_image();
+    assert_eq!(true_value.into_vec(), inside.into_vec())
+}
+
+#[cfg(feature = "tensor")]
+#[test]
+fn test_read_region_tensor() {
+    let slide = get_slide();
+    let layout = utils::TensorLayout::Nchw;
+    let (value, shape) = match slide.read_region_tensor((1000, 1000), 0, (10, 20), layout, None) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    assert_eq!([1, 3, 10, 20], shape);
+    let region = match slide.read_region(1000u32, 1000u32, 0u32, 10u32, 20u32) {
+        Ok(val) => val,
+        Err(msg) => panic!("Read region error:\n{}", msg),
+    };
+    // Green plane
+    let true_value: Vec<f32> =
+        region.pixels().map(|pixel| f32::from(pixel.data[1]) / 255.0).collect();
+    assert_eq!(true_value, value[200..400].to_vec())
+}
+
+#[test]
+fn test_get_error() {
+    let slide = get_slide();
+    assert_eq!(None, slide.get_error());
+
+    let osr = match openslide::bindings::open("assets/CMU-1-Small-Region.svs") {
+        Ok(val) => val,
+        Err(msg) => panic!("Unable to load slide:\n{}", msg),
+    };
+    unsafe {
+        // OpenSlide rejects a negative width, and the handle is unusable afterwards
+        let _ = openslide::bindings::read_region(osr, 0, 0, 0, -1, 0);
+        let msg = match openslide::bindings::get_error(osr) {
+            Some(val) => val,
+            None => panic!("Expected an error after reading a region with a negative width"),
+        };
+        assert!(msg.contains("negative width"));
+        assert_eq!((-1, -1), openslide::bindings::get_level_dimensions(osr, 0).unwrap());
+        openslide::bindings::close(osr);
+    }
+}
+
+#[test]
+fn test_error_carries_openslide_diagnostic() {
+    // A copy of the slide with the JPEG data of its first level 0 tile (bytes 16 to 3700 of the
+    // file) zeroed, so that OpenSlide opens it, but fails to decode that tile
+    let mut data = std::fs::read("assets/CMU-1-Small-Region.svs").unwrap();
+    for byte in &mut data[16..16 + 3684] {
+        *byte = 0;
+    }
+    let filename = std::env::temp_dir().join("openslide_test_error_diagnostic.svs");
+    std::fs::write(&filename, &data).unwrap();
+    let slide = match openslide::OpenSlide::new(&filename) {
+        Ok(val) => val,
+        Err(msg) => panic!("Unable to load slide:\n{}", msg),
+    };
+
+    let err = match slide.read_region(0u32, 0u32, 0u32, 10u32, 10u32) {
+        Ok(_) => panic!("Expected an error when reading a corrupt tile"),
+        Err(err) => err,
+    };
+    let diagnostic = match slide.get_error() {
+        Some(val) => val,
+        None => panic!("Expected OpenSlide to record an error for a corrupt tile"),
+    };
+    match err {
+        openslide::OpenSlideError::OpenSlideInternal(ref msg) => {
+            assert!(msg.contains(&diagnostic));
+            assert!(msg.contains("level 0 with top left (row 0, col 0)"));
+        }
+        _ => panic!("Expected an OpenSlide error, got:\n{}", err),
+    }
+
+    // The handle is unusable afterwards, and later calls report the same diagnostic
+    match slide.get_level_dimensions(0) {
+        Ok(_) => panic!("Expected an error from an unusable handle"),
+        Err(err) => assert!(err.to_string().contains(&diagnostic)),
+    }
+    std::fs::remove_file(&filename).unwrap();
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_read_regions_par() {
+    let slide = get_slide();
+    let regions: Vec<openslide::Region> = (0..10)
+        .map(|index| openslide::Region {
+            top_left_lvl0_row: 100 * index,
+            top_left_lvl0_col: 200,
+            level: 0,
+            height: 4,
+            width: 6,
+        })
+        .collect();
+    let values = slide.read_regions_par(&regions, 3);
+    assert_eq!(regions.len(), values.len());
+    for (region, value) in regions.iter().zip(values) {
+        let value = match value {
+            Ok(val) => val,
+            Err(msg) => panic!("Parallel read error:\n{}", msg),
+        };
+        let true_value = slide.read_region(region.top_left_lvl0_row, 200, 0, 4, 6).unwrap();
+        assert_eq!(true_value.into_vec(), value.into_vec());
+    }
+    assert!(slide.read_regions_par(&[], 0).is_empty());
+}
+
+#[test]
+fn test_sync_openslide() {
+    fn assert_send<T: Send>() {}
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send::<openslide::OpenSlide>();
+    assert_send_sync::<openslide::SyncOpenSlide>();
+
+    let slide = match openslide::SyncOpenSlide::new(Path::new("assets/CMU-1-Small-Region.svs")) {
+        Ok(val) => val,
+        Err(msg) => panic!("Unable to load slide:\n{}", msg),
+    };
+    assert_eq!(Some(String::from("aperio")), slide.properties.vendor());
+    let slide = Arc::new(slide);
+    let threads: Vec<_> = (0..4u64)
+        .map(|index| {
+            let slide = slide.clone();
+            std::thread::spawn(move || slide.read_region(100 * index, 200, 0, 4, 6))
+        })
+        .collect();
+    let true_slide = get_slide();
+    for (index, thread) in threads.into_iter().enumerate() {
+        let value = match thread.join().unwrap() {
+            Ok(val) => val,
+            Err(msg) => panic!("Read region error:\n{}", msg),
+        };
+        let true_value = true_slide.read_region(100 * index as u64, 200, 0, 4, 6).unwrap();
+        assert_eq!(true_value.into_vec(), value.into_vec());
+    }
+    assert_eq!((2220, 2967), slide.get_level0_dimensions().unwrap());
+}
+
+#[test]
+fn test_cached_openslide() {
+    let capacity = openslide::CacheCapacity::Regions(2);
+    let slide = openslide::CachedOpenSlide::new(get_slide(), capacity);
+    let region = |row| openslide::Region {
+        top_left_lvl0_row: row,
+        top_left_lvl0_col: 200,
+        level: 0,
+        height: 4,
+        width: 6,
+    };
+    let value = match slide.read_region(100, 200, 0, 4, 6) {
+        Ok(val) => val,
+        Err(msg) => panic!("Cached read error:\n{}", msg),
+    };
+    let true_value = slide.slide().read_region(100u32, 200u32, 0u32, 4u32, 6u32).unwrap();
+    assert_eq!(true_value.into_vec(), value.clone().into_vec());
+    // A hit returns the same image
+    assert_eq!(value.into_vec(), slide.read_region(100, 200, 0, 4, 6).unwrap().into_vec());
+    assert_eq!((1, 4 * 6 * 4), (slide.len(), slide.num_bytes()));
+    let stats = openslide::CacheStats {
+        hits: 1,
+        misses: 1,
+        evictions: 0,
+        bytes: 4 * 6 * 4,
+    };
+    assert_eq!(stats, slide.stats());
+
+    // The least recently used region is evicted
+    slide.read_region(300, 200, 0, 4, 6).unwrap();
+    slide.read_region(100, 200, 0, 4, 6).unwrap();
+    slide.read_region(500, 200, 0, 4, 6).unwrap();
+    assert_eq!(2, slide.len());
+    assert!(slide.contains(&region(100)));
+    assert!(!slide.contains(&region(300)));
+    assert!(slide.contains(&region(500)));
+    let stats = openslide::CacheStats {
+        hits: 2,
+        misses: 3,
+        evictions: 1,
+        bytes: 2 * 4 * 6 * 4,
+    };
+    assert_eq!(stats, slide.stats());
+
+    slide.clear();
+    assert!(slide.is_empty());
+    assert_eq!(openslide::CacheStats { bytes: 0, ..stats }, slide.stats());
+    slide.reset_stats();
+    assert_eq!(openslide::CacheStats::default(), slide.stats());
+
+    // A region larger than the capacity is not cached
+    let slide = openslide::CachedOpenSlide::new(get_slide(), openslide::CacheCapacity::Bytes(50));
+    slide.read_region(100, 200, 0, 4, 6).unwrap();
+    assert!(slide.is_empty());
+}
+
+#[test]
+fn test_new_as_ref_path() {
+    let filename = "assets/CMU-1-Small-Region.svs";
+    let expected = get_slide().get_level0_dimensions().unwrap();
+    let from_str = match openslide::OpenSlide::new(filename) {
+        Ok(val) => val,
+        Err(msg) => panic!("Unable to load slide:\n{}", msg),
+    };
+    assert_eq!(expected, from_str.get_level0_dimensions().unwrap());
+    let from_string = openslide::OpenSlide::new(String::from(filename)).unwrap();
+    assert_eq!(expected, from_string.get_level0_dimensions().unwrap());
+    let from_path_buf = openslide::OpenSlide::new(PathBuf::from(filename)).unwrap();
+    assert_eq!(expected, from_path_buf.get_level0_dimensions().unwrap());
+}
@@ -0,0 +1,150 @@
+//! Integration tests of the Deep Zoom generator, against a mock slide
+//!
+
+extern crate image;
+extern crate openslide;
+
+use std::cell::RefCell;
+
+use image::{Rgba, RgbaImage};
+use openslide::{DeepZoomGenerator, OpenSlideError, SlideReader};
+
+/// (row, column, level, height, width) of a read
+type Read = (u64, u64, u32, u64, u64);
+
+/// A slide of 1000 x 600 pixels, with a second level downsampled by 4, that records its reads
+struct MockSlide {
+    bounds: Option<(u64, u64, u64, u64)>,
+    reads: RefCell<Vec<Read>>,
+}
+
+impl MockSlide {
+    fn new(bounds: Option<(u64, u64, u64, u64)>) -> MockSlide {
+        MockSlide {
+            bounds,
+            reads: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn last_read(&self) -> Read {
+        *self.reads.borrow().last().expect("The slide was read")
+    }
+}
+
+impl SlideReader for MockSlide {
+    fn get_level_count(&self) -> Result<u32, OpenSlideError> {
+        Ok(2)
+    }
+
+    fn get_level_dimensions(&self, level: u32) -> Result<(u64, u64), OpenSlideError> {
+        match level {
+            0 => Ok((1000, 600)),
+            1 => Ok((250, 150)),
+            _ => Err(OpenSlideError::LevelOutOfRange {
+                requested: level,
+                max: 1,
+            }),
+        }
+    }
+
+    fn get_level_downsample(&self, level: u32) -> Result<f64, OpenSlideError> {
+        match level {
+            0 => Ok(1.0),
+            1 => Ok(4.0),
+            _ => Err(OpenSlideError::LevelOutOfRange {
+                requested: level,
+                max: 1,
+            }),
+        }
+    }
+
+    fn read_region(
+        &self,
+        top_left_lvl0_row: u64,
+        top_left_lvl0_col: u64,
+        level: u32,
+        height: u64,
+        width: u64,
+    ) -> Result<RgbaImage, OpenSlideError> {
+        self.reads
+            .borrow_mut()
+            .push((top_left_lvl0_row, top_left_lvl0_col, level, height, width));
+        Ok(RgbaImage::from_pixel(
+            width as u32,
+            height as u32,
+            Rgba([10, 20, 30, 255]),
+        ))
+    }
+
+    fn get_bounds(&self) -> Result<Option<(u64, u64, u64, u64)>, OpenSlideError> {
+        Ok(self.bounds)
+    }
+}
+
+fn get_generator(slide: &MockSlide) -> DeepZoomGenerator<'_, MockSlide> {
+    match DeepZoomGenerator::new(slide, 254, 1, true) {
+        Ok(val) => val,
+        Err(msg) => panic!("Deep Zoom generator error:\n{}", msg),
+    }
+}
+
+#[test]
+fn test_deep_zoom_levels() {
+    let slide = MockSlide::new(None);
+    let generator = get_generator(&slide);
+    assert_eq!(11, generator.level_count());
+    assert_eq!((1000, 600), generator.level_dimensions(10).unwrap());
+    assert_eq!((500, 300), generator.level_dimensions(9).unwrap());
+    assert_eq!((63, 38), generator.level_dimensions(6).unwrap());
+    assert_eq!((1, 1), generator.level_dimensions(0).unwrap());
+    assert_eq!((4, 3), generator.level_tiles(10).unwrap());
+    assert_eq!((2, 2), generator.level_tiles(9).unwrap());
+    assert_eq!((1, 1), generator.level_tiles(8).unwrap());
+    assert!(generator.level_dimensions(11).is_err());
+    assert!(DeepZoomGenerator::new(&slide, 254, 255, false).is_err());
+}
+
+#[test]
+fn test_deep_zoom_tile_reads() {
+    let slide = MockSlide::new(None);
+    let generator = get_generator(&slide);
+
+    // The last tile of the highest level, with overlap above and left of it only
+    let tile = generator.get_tile(10, (3, 2)).unwrap();
+    assert_eq!((239, 93), tile.dimensions());
+    assert_eq!((507, 761, 0, 93, 239), slide.last_read());
+
+    // Read from level 0 at twice the size, and resized
+    let tile = generator.get_tile(9, (1, 0)).unwrap();
+    assert_eq!((247, 255), tile.dimensions());
+    assert_eq!((0, 506, 0, 510, 494), slide.last_read());
+
+    // Read from level 1, whose downsample matches
+    let tile = generator.get_tile(8, (0, 0)).unwrap();
+    assert_eq!((250, 150), tile.dimensions());
+    assert_eq!((0, 0, 1, 150, 250), slide.last_read());
+
+    assert_eq!(3, slide.reads.borrow().len());
+    assert!(generator.get_tile(10, (4, 0)).is_err());
+    assert!(generator.get_tile(11, (0, 0)).is_err());
+    assert_eq!(3, slide.reads.borrow().len());
+}
+
+#[test]
+fn test_deep_zoom_limit_bounds() {
+    let slide = MockSlide::new(Some((100, 50, 500, 300)));
+    let generator = get_generator(&slide);
+    assert_eq!(
+        (500, 300),
+        generator
+            .level_dimensions(generator.level_count() - 1)
+            .unwrap()
+    );
+
+    let tile = generator
+        .get_tile(generator.level_count() - 1, (0, 0))
+        .unwrap();
+    assert_eq!((255, 255), tile.dimensions());
+    // Offset by the level 0 (x, y) of the bounds
+    assert_eq!((50, 100, 0, 255, 255), slide.last_read());
+}
@@ -0,0 +1,89 @@
+//! Integration tests of the error type
+//!
+
+extern crate openslide;
+
+use std::error::Error;
+use std::io;
+use std::path::PathBuf;
+
+use openslide::OpenSlideError;
+
+#[test]
+fn test_error_display() {
+    let err = OpenSlideError::LevelOutOfRange {
+        requested: 3,
+        max: 1,
+    };
+    assert_eq!(
+        err.to_string(),
+        "Error: Specified level 3 is larger than the max slide level 1"
+    );
+
+    let err = OpenSlideError::PropertyParse {
+        key: String::from("openslide.mpp-x"),
+        value: String::from("abc"),
+    };
+    assert_eq!(
+        err.to_string(),
+        "Error: Unable to parse property openslide.mpp-x with value abc"
+    );
+}
+
+#[test]
+fn test_truncated_read_display() {
+    let err = OpenSlideError::TruncatedRead {
+        level: 1,
+        row: -4,
+        col: 8,
+        height: 16,
+        width: 32,
+        message: String::from("Unexpected end of file"),
+    };
+    assert_eq!(
+        err.to_string(),
+        "Error: Truncated read of region at level 1 with top left (row -4, col 8) and \
+         (height, width) (16, 32): Unexpected end of file"
+    );
+    assert_eq!(io::Error::from(err).kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn test_error_into_io_error() {
+    let err = io::Error::from(OpenSlideError::NonexistentPath(PathBuf::from("missing.svs")));
+    assert_eq!(err.kind(), io::ErrorKind::NotFound);
+
+    let err = io::Error::from(OpenSlideError::LevelOutOfRange {
+        requested: 3,
+        max: 1,
+    });
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    let inner = io::Error::new(io::ErrorKind::PermissionDenied, "denied");
+    let err = io::Error::from(OpenSlideError::from(inner));
+    assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+}
+
+#[test]
+fn test_error_source() {
+    let err = OpenSlideError::from(io::Error::new(io::ErrorKind::PermissionDenied, "inner"));
+    assert!(err.source().is_some());
+    assert!(OpenSlideError::OpenFailed(String::from("slide.svs")).source().is_none());
+}
+
+#[test]
+fn test_error_send_sync_static() {
+    // The bound of the blanket `From` implementations of `anyhow::Error` and
+    // `Box<dyn Error + Send + Sync>`
+    fn assert_convertible<T: Error + Send + Sync + 'static>() {}
+    assert_convertible::<OpenSlideError>();
+
+    fn fails() -> Result<(), Box<dyn Error + Send + Sync>> {
+        Err(OpenSlideError::MissingProperty(String::from("openslide.mpp-x")))?;
+        Ok(())
+    }
+    match fails() {
+        Ok(_) => panic!("Expected an error"),
+        Err(err) => assert!(err.downcast_ref::<OpenSlideError>().is_some()),
+    }
+}
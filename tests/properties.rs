@@ -0,0 +1,233 @@
+//! Integration tests of the properties module
+//!
+
+extern crate openslide;
+
+use std::collections::HashMap;
+
+use openslide::properties::Properties;
+use openslide::OpenSlideError;
+
+fn property_map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|&(key, value)| (String::from(key), String::from(value)))
+        .collect()
+}
+
+fn parse_properties(pairs: &[(&str, &str)]) -> Properties {
+    match Properties::new(&property_map(pairs)) {
+        Ok(val) => val,
+        Err(msg) => panic!("Properties error:\n{}", msg),
+    }
+}
+
+#[test]
+fn test_hamamatsu_properties() {
+    let properties = parse_properties(&[
+        ("hamamatsu.SourceLens", "20"),
+        ("hamamatsu.XOffsetFromSlideCentre", "-1251187"),
+        ("hamamatsu.YOffsetFromSlideCentre", "3693857"),
+        ("hamamatsu.NoLayers", "1"),
+        ("hamamatsu.MacroImage", "slide_macro.jpg"),
+    ]);
+    assert_eq!(properties.source_lens(), Some(20.0));
+    assert_eq!(properties.x_offset_from_slide_centre(), Some(-1_251_187));
+    assert_eq!(properties.y_offset_from_slide_centre(), Some(3_693_857));
+    assert_eq!(properties.no_layers(), Some(1));
+    assert_eq!(
+        properties.macro_image(),
+        Some(String::from("slide_macro.jpg"))
+    );
+    assert_eq!(properties.objective(), None);
+}
+
+#[test]
+fn test_leica_properties() {
+    let properties = parse_properties(&[
+        ("leica.aperture", "0.4"),
+        ("leica.creation-date", "2010-10-29T16:15:51.517Z"),
+        ("leica.device-model", "Leica SCN400"),
+        ("leica.illumination-source", "brightfield"),
+        ("leica.objective", "20"),
+    ]);
+    assert_eq!(properties.aperture(), Some(0.4));
+    assert_eq!(properties.creation_date(), Some(String::from("2010-10-29T16:15:51.517Z")));
+    assert_eq!(properties.device_model(), Some(String::from("Leica SCN400")));
+    assert_eq!(properties.illumination_source(), Some(String::from("brightfield")));
+    assert_eq!(properties.leica_objective(), Some(20.0));
+    assert_eq!(properties.barcode(), None);
+}
+
+#[test]
+fn test_philips_properties() {
+    let properties = parse_properties(&[
+        ("philips.DICOM_PIXEL_SPACING", "\"0.000226891\" \"0.000226907\""),
+        ("philips.DICOM_DERIVATION_DESCRIPTION", "PHILIPS UFS V1.7.1.2 | Quality=2"),
+        ("philips.DICOM_BITS_ALLOCATED", "8"),
+        ("philips.DICOM_LOSSY_IMAGE_COMPRESSION_RATIO", "15"),
+    ]);
+    assert_eq!(properties.dicom_pixel_spacing(), Some((0.000_226_891, 0.000_226_907)));
+    assert_eq!(
+        properties.dicom_derivation_description(),
+        Some(String::from("PHILIPS UFS V1.7.1.2 | Quality=2"))
+    );
+    assert_eq!(properties.dicom_bits_allocated(), Some(8));
+    assert_eq!(properties.dicom_lossy_image_compression_ratio(), Some(15.0));
+    assert_eq!(properties.dicom_manufacturer(), None);
+}
+
+#[test]
+fn test_philips_malformed_pixel_spacing() {
+    let properties = parse_properties(&[("philips.DICOM_PIXEL_SPACING", "\"0.000226891\"")]);
+    assert_eq!(properties.dicom_pixel_spacing(), None);
+}
+
+#[test]
+fn test_standard_properties() {
+    let properties = parse_properties(&[
+        ("openslide.vendor", "aperio"),
+        ("openslide.mpp-x", "0.499"),
+        ("openslide.objective-power", "20"),
+        ("openslide.background-color", "FFC0A0"),
+        ("openslide.bounds-x", "120"),
+        ("openslide.bounds-height", "2000"),
+        ("openslide.level-count", "2"),
+        ("openslide.level[0].width", "2220"),
+        ("openslide.level[0].height", "2967"),
+        ("openslide.level[1].width", "555"),
+        ("openslide.level[1].downsample", "4"),
+    ]);
+    assert_eq!(properties.vendor(), Some(String::from("aperio")));
+    assert_eq!(properties.mpp_x(), Some(0.499));
+    assert_eq!(properties.objective_power(), Some(20));
+    assert_eq!(properties.background_color(), Some([255, 192, 160]));
+    assert_eq!(properties.bounds_x(), Some(120));
+    assert_eq!(properties.bounds_y(), None);
+    assert_eq!(properties.bounds_height(), Some(2000));
+    assert_eq!(properties.level_count(), Some(2));
+
+    let levels = match properties.levels() {
+        Some(val) => val,
+        None => panic!("Expected level properties"),
+    };
+    assert_eq!(levels.len(), 2);
+    assert_eq!(levels[0].width(), Some(2220));
+    assert_eq!(levels[0].height(), Some(2967));
+    assert_eq!(levels[1].width(), Some(555));
+    assert_eq!(levels[1].downsample(), Some(4.0));
+}
+
+#[test]
+fn test_aperio_properties() {
+    let properties = parse_properties(&[
+        ("aperio.MPP", "0.4990"),
+        ("aperio.AppMag", "20"),
+        ("aperio.ScanScope ID", "CPAPERIOCS"),
+    ]);
+    assert_eq!(properties.mpp(), Some(0.499));
+    assert_eq!(properties.app_mag(), Some(20));
+    assert_eq!(properties.scan_scope_id(), Some(String::from("CPAPERIOCS")));
+    assert!(properties.aperio().is_some());
+    assert!(properties.hamamatsu().is_none());
+}
+
+/// Check that parsing a property with a malformed value fails with an error naming it.
+fn assert_malformed(key: &str, value: &str) {
+    let map = property_map(&[(key, value)]);
+    match Properties::new(&map) {
+        Ok(_) => panic!("Expected an error for a malformed {}", key),
+        Err(OpenSlideError::PropertyParse { key: err_key, value: err_value }) => {
+            assert_eq!(err_key, key);
+            assert_eq!(err_value, value);
+        }
+        Err(msg) => panic!("Unexpected error:\n{}", msg),
+    }
+}
+
+#[test]
+fn test_aperio_malformed_value() {
+    let map = property_map(&[("aperio.MPP", "not-a-number")]);
+    match Properties::new(&map) {
+        Ok(_) => panic!("Expected an error for a malformed aperio.MPP"),
+        Err(OpenSlideError::PropertyParse { key, value }) => {
+            assert_eq!(key, "aperio.MPP");
+            assert_eq!(value, "not-a-number");
+        }
+        Err(msg) => panic!("Unexpected error:\n{}", msg),
+    }
+}
+
+#[test]
+fn test_openslide_malformed_values() {
+    for key in &[
+        "openslide.mpp-x",
+        "openslide.mpp-y",
+        "openslide.objective-power",
+        "openslide.bounds-x",
+        "openslide.bounds-y",
+        "openslide.bounds-width",
+        "openslide.bounds-height",
+        "openslide.level-count",
+        "openslide.level[0].downsample",
+        "openslide.level[0].height",
+        "openslide.level[0].width",
+        "openslide.level[0].tile-height",
+        "openslide.level[0].tile-width",
+    ] {
+        assert_malformed(key, "not-a-number");
+    }
+    assert_malformed("openslide.objective-power", "40.0");
+    assert_malformed("openslide.bounds-x", "-1");
+    assert_malformed("openslide.background-color", "FFF");
+    assert_malformed("openslide.background-color", "12345G");
+}
+
+#[test]
+fn test_hamamatsu_malformed_values() {
+    for key in &[
+        "hamamatsu.SourceLens",
+        "hamamatsu.XOffsetFromSlideCentre",
+        "hamamatsu.YOffsetFromSlideCentre",
+        "hamamatsu.NoLayers",
+        "hamamatsu.LayerSpacing",
+        "hamamatsu.NoJpegColumns",
+        "hamamatsu.NoJpegRows",
+        "hamamatsu.PhysicalWidth",
+        "hamamatsu.PhysicalHeight",
+    ] {
+        assert_malformed(key, "not-a-number");
+    }
+}
+
+#[test]
+fn test_leica_malformed_values() {
+    for key in &["leica.aperture", "leica.objective"] {
+        assert_malformed(key, "not-a-number");
+    }
+}
+
+#[test]
+fn test_philips_multi_valued() {
+    let properties = parse_properties(&[
+        ("philips.DICOM_BITS_ALLOCATED", "\"8\" \"8\""),
+        ("philips.DICOM_LOSSY_IMAGE_COMPRESSION_RATIO", "\"15\" \"15\""),
+    ]);
+    assert_eq!(properties.dicom_bits_allocated(), Some(8));
+    assert_eq!(properties.dicom_lossy_image_compression_ratio(), Some(15.0));
+}
+
+#[test]
+fn test_philips_malformed_values() {
+    for key in &[
+        "philips.DICOM_BITS_ALLOCATED",
+        "philips.DICOM_BITS_STORED",
+        "philips.DICOM_HIGH_BIT",
+        "philips.DICOM_SAMPLES_PER_PIXEL",
+        "philips.DICOM_LOSSY_IMAGE_COMPRESSION_RATIO",
+    ] {
+        assert_malformed(key, "not-a-number");
+        assert_malformed(key, "\"3\" \"x\"");
+        assert_malformed(key, "");
+    }
+}
@@ -0,0 +1,434 @@
+//! Integration tests of the utils module
+//!
+
+extern crate image;
+extern crate openslide;
+
+use image::{ImageBuffer, ImageFormat, Rgb, RgbImage, Rgba, RgbaImage};
+use openslide::utils;
+
+#[test]
+fn test_box_downsample() {
+    let mut image = RgbaImage::new(5, 2);
+    image.put_pixel(0, 0, Rgba([0, 10, 100, 255]));
+    image.put_pixel(1, 0, Rgba([1, 20, 100, 255]));
+    image.put_pixel(0, 1, Rgba([2, 30, 200, 255]));
+    image.put_pixel(1, 1, Rgba([3, 40, 200, 255]));
+    let value = match utils::box_downsample(&image, 2) {
+        Ok(val) => val,
+        Err(msg) => panic!("Box downsample error:\n{}", msg),
+    };
+    assert_eq!((2, 1), value.dimensions());
+    assert_eq!(&Rgba([2, 25, 150, 255]), value.get_pixel(0, 0));
+    assert_eq!(&Rgba([0, 0, 0, 0]), value.get_pixel(1, 0));
+}
+
+#[test]
+fn test_box_downsample_zero_factor() {
+    let image = RgbaImage::new(2, 2);
+    assert!(utils::box_downsample(&image, 0).is_err())
+}
+
+#[test]
+fn test_supported_extensions() {
+    let value = openslide::supported_extensions();
+    let true_value = vec![".svs", ".tif", ".vms", ".vmu", ".ndpi", ".scn", ".mrxs", ".tiff",
+                          ".svslide", ".bif"];
+    assert_eq!(true_value, value)
+}
+
+#[test]
+fn test_split_alpha() {
+    let mut image = RgbaImage::new(2, 1);
+    image.put_pixel(0, 0, Rgba([10, 20, 30, 255]));
+    image.put_pixel(1, 0, Rgba([40, 50, 60, 0]));
+    let (rgb_image, alpha_mask) = utils::split_alpha(&image);
+    assert_eq!(vec![10, 20, 30, 40, 50, 60], rgb_image.into_vec());
+    assert_eq!(vec![255, 0], alpha_mask.into_vec())
+}
+
+#[test]
+fn test_foreground_fraction() {
+    let mut image = RgbaImage::new(4, 1);
+    image.put_pixel(0, 0, Rgba([250, 250, 250, 255]));
+    image.put_pixel(1, 0, Rgba([200, 100, 180, 255]));
+    image.put_pixel(2, 0, Rgba([200, 100, 180, 0]));
+    image.put_pixel(3, 0, Rgba([120, 60, 140, 255]));
+    let value = utils::foreground_fraction(&image, 0.1);
+    assert!((value - 0.5).abs() < 1e-12)
+}
+
+#[test]
+fn test_rgba_to_nv12() {
+    let mut image = RgbaImage::new(3, 2);
+    for (col, _, pixel) in image.enumerate_pixels_mut() {
+        *pixel = if col < 2 { Rgba([255, 0, 0, 255]) } else { Rgba([255, 255, 255, 255]) };
+    }
+    let (y_plane, uv_plane) = utils::rgba_to_nv12(&image, utils::YuvMatrix::Bt601);
+    assert_eq!(vec![81, 81, 235, 81, 81, 235], y_plane);
+    assert_eq!(vec![90, 240, 128, 128], uv_plane)
+}
+
+#[test]
+fn test_perceptual_hash() {
+    let mut image = RgbaImage::new(64, 64);
+    for (col, row, pixel) in image.enumerate_pixels_mut() {
+        let wave = (col as f64 / 9.0).sin() * (row as f64 / 13.0).cos();
+        let value = (90.0 + 80.0 * wave + row as f64) as u8;
+        *pixel = Rgba([value, value, value, 255]);
+    }
+    let mut changed = image.clone();
+    changed.put_pixel(10, 10, Rgba([255, 0, 0, 255]));
+
+    let hash = utils::perceptual_hash(&image);
+    assert_eq!(hash, utils::perceptual_hash(&image.clone()));
+    assert!(utils::hamming_distance(hash, utils::perceptual_hash(&changed)) <= 4);
+    assert!(utils::hamming_distance(hash, !hash) == 64)
+}
+
+#[test]
+fn test_hamming_distance() {
+    assert_eq!(0, utils::hamming_distance(0b1011, 0b1011));
+    assert_eq!(3, utils::hamming_distance(0b1011, 0b0110))
+}
+
+#[test]
+fn test_dither_to_8bit() {
+    let mut image = ImageBuffer::<Rgba<u16>, Vec<u16>>::new(4, 4);
+    for pixel in image.pixels_mut() {
+        *pixel = Rgba([25700, 25829, 0, 65535]);
+    }
+    let value = utils::dither_to_8bit(&image);
+    let reds: Vec<u8> = value.pixels().map(|pixel| pixel.data[0]).collect();
+    let greens: Vec<u8> = value.pixels().map(|pixel| pixel.data[1]).collect();
+    assert!(reds.iter().all(|red| *red == 100));
+    assert_eq!(8, greens.iter().filter(|green| **green == 100).count());
+    assert_eq!(8, greens.iter().filter(|green| **green == 101).count());
+    assert!(value.pixels().all(|pixel| pixel.data[2] == 0 && pixel.data[3] == 255))
+}
+
+#[test]
+fn test_decode_buffer_short_buffer() {
+    let buffer = vec![0xff00_0000u32; 3];
+    let word_repr = utils::WordRepresentation::BigEndian;
+    assert!(utils::decode_buffer(&buffer, 2u32, 2u32, word_repr).is_err())
+}
+
+#[test]
+fn test_to_data_uri() {
+    let image = RgbaImage::from_pixel(3, 2, Rgba([10, 20, 30, 255]));
+    let value = match utils::to_data_uri(&image, ImageFormat::PNG) {
+        Ok(val) => val,
+        Err(msg) => panic!("Data URI error:\n{}", msg),
+    };
+    let payload = value.trim_start_matches("data:image/png;base64,");
+    assert!(value.starts_with("data:image/png;base64,"));
+    assert!(payload.starts_with("iVBORw0KGgo")); // The PNG signature
+    assert_eq!(0, payload.len() % 4);
+    assert!(utils::to_data_uri(&image, ImageFormat::TIFF).is_err())
+}
+
+#[test]
+fn test_best_level_for_non_power_of_two_downsamples() {
+    let level_downsamples = [1.0, 4.000_1, 16.000_3, 48.3];
+    assert_eq!(0, utils::best_level_for_downsample(&level_downsamples, 0.5));
+    assert_eq!(0, utils::best_level_for_downsample(&level_downsamples, 3.9));
+    assert_eq!(1, utils::best_level_for_downsample(&level_downsamples, 4.0));
+    assert_eq!(2, utils::best_level_for_downsample(&level_downsamples, 16.0));
+    assert_eq!(2, utils::best_level_for_downsample(&level_downsamples, 16.1));
+    assert_eq!(2, utils::best_level_for_downsample(&level_downsamples, 48.0));
+    assert_eq!(3, utils::best_level_for_downsample(&level_downsamples, 1000.0));
+}
+
+#[test]
+fn test_level_coordinates_for_non_power_of_two_downsamples() {
+    assert_eq!(2500, utils::lvl0_to_level_coordinate(10_000, 4.000_1));
+    assert_eq!(10_000, utils::level_to_lvl0_coordinate(2500, 4.000_1));
+    assert_eq!(2, utils::lvl0_to_level_coordinate(7, 3.0));
+    assert_eq!(3, utils::lvl0_to_level_coordinate(5, 2.0)); // Halfway rounds away from zero
+    assert_eq!(8, utils::level_to_lvl0_coordinate(3, 2.5));
+    assert_eq!(0, utils::lvl0_to_level_coordinate(0, 16.000_3))
+}
+
+#[test]
+fn test_rgba_to_gray() {
+    let mut image = RgbaImage::from_pixel(2, 1, Rgba([0, 0, 0, 0]));
+    image.put_pixel(1, 0, Rgba([0, 0, 0, 255]));
+    let background = Rgb([255, 255, 255]);
+
+    let value = utils::rgba_to_gray(&image, background, utils::TransparentGray::Background);
+    assert_eq!(255, value.get_pixel(0, 0).data[0]);
+    assert_eq!(0, value.get_pixel(1, 0).data[0]);
+
+    let value = utils::rgba_to_gray(&image, background, utils::TransparentGray::Sentinel(7));
+    assert_eq!(7, value.get_pixel(0, 0).data[0]);
+    assert_eq!(0, value.get_pixel(1, 0).data[0]);
+
+    let half = RgbaImage::from_pixel(1, 1, Rgba([0, 0, 0, 128]));
+    let value = utils::rgba_to_gray(&half, background, utils::TransparentGray::Sentinel(7));
+    assert_eq!(127, value.get_pixel(0, 0).data[0])
+}
+
+#[test]
+fn test_resize_to_fit() {
+    let image = RgbaImage::from_pixel(40, 10, Rgba([10, 20, 30, 255]));
+    assert_eq!((20, 5), utils::resize_to_fit(&image, 20, 20).dimensions());
+    assert_eq!((8, 2), utils::resize_to_fit(&image, 30, 2).dimensions());
+    assert_eq!((40, 10), utils::resize_to_fit(&image, 50, 50).dimensions());
+    assert_eq!((10, 1), utils::resize_to_fit(&RgbaImage::new(100, 1), 10, 10).dimensions())
+}
+
+#[test]
+fn test_decode_buffer_rgba8() {
+    let buffer = vec![0xff01_0203u32, 0x8040_2010, 0x0000_0000, 0xff0a_0b0c];
+    let word_repr = utils::WordRepresentation::Native;
+    let value = match utils::decode_buffer_rgba8(&buffer, 2u32, 2u32, word_repr) {
+        Ok(val) => val,
+        Err(msg) => panic!("Decode buffer error:\n{}", msg),
+    };
+    assert_eq!((2, 2), (value.width, value.height));
+    assert_eq!(vec![1, 2, 3, 255], value.data[0..4].to_vec());
+    assert_eq!(vec![0, 0, 0, 0], value.data[8..12].to_vec());
+
+    let image = match utils::decode_buffer(&buffer, 2u32, 2u32, word_repr) {
+        Ok(val) => val,
+        Err(msg) => panic!("Decode buffer error:\n{}", msg),
+    };
+    match value.into_rgba_image() {
+        Ok(converted) => assert_eq!(image.into_vec(), converted.into_vec()),
+        Err(msg) => panic!("Conversion error:\n{}", msg),
+    }
+}
+
+#[test]
+fn test_difference_image() {
+    let image = RgbaImage::from_pixel(2, 1, Rgba([200, 10, 100, 255]));
+    let base = RgbaImage::from_pixel(2, 1, Rgba([10, 200, 100, 0]));
+    let value = match utils::difference_image(&image, &base) {
+        Ok(val) => val,
+        Err(msg) => panic!("Difference image error:\n{}", msg),
+    };
+    assert_eq!(Rgba([255, 0, 128, 255]), *value.get_pixel(1, 0));
+    assert!(utils::difference_image(&image, &RgbaImage::new(1, 1)).is_err())
+}
+
+#[test]
+fn test_composite_over() {
+    let mut image = RgbaImage::from_pixel(2, 1, Rgba([0, 0, 0, 0]));
+    image.put_pixel(1, 0, Rgba([200, 100, 0, 128]));
+    let value = utils::composite_over(&image, Rgb([255, 255, 255]));
+    assert_eq!(Rgb([255, 255, 255]), *value.get_pixel(0, 0));
+    assert_eq!(Rgb([227, 177, 127]), *value.get_pixel(1, 0))
+}
+
+#[test]
+fn test_blend() {
+    let image = RgbaImage::from_pixel(2, 2, Rgba([0, 100, 200, 255]));
+    let other = RgbaImage::from_pixel(2, 2, Rgba([100, 100, 0, 55]));
+    let value = match utils::blend(&image, &other, 0.25) {
+        Ok(val) => val,
+        Err(msg) => panic!("Blend error:\n{}", msg),
+    };
+    assert_eq!(Rgba([25, 100, 150, 205]), *value.get_pixel(1, 1));
+    assert!(utils::blend(&image, &other, 1.5).is_err());
+    assert!(utils::blend(&image, &RgbaImage::new(1, 2), 0.5).is_err())
+}
+
+#[test]
+fn test_decode_buffer_f32() {
+    let buffer = vec![0xff33_6699u32, 0x8040_2000];
+    let word_repr = utils::WordRepresentation::Native;
+    let value = match utils::decode_buffer_f32(&buffer, 1u32, 2u32, word_repr) {
+        Ok(val) => val,
+        Err(msg) => panic!("Decode buffer error:\n{}", msg),
+    };
+    assert_eq!((2, 1), value.dimensions());
+    assert_eq!([0.2, 0.4, 0.6, 1.0], value.get_pixel(0, 0).data);
+    // Premultiplied values are kept as is
+    let [red, green, blue, alpha] = value.get_pixel(1, 0).data;
+    assert_eq!((64.0 / 255.0, 32.0 / 255.0, 0.0, 128.0 / 255.0), (red, green, blue, alpha));
+    assert!(utils::decode_buffer_f32(&buffer, 2u32, 2u32, word_repr).is_err())
+}
+
+#[test]
+fn test_color_deconvolve() {
+    let mut image = RgbImage::from_pixel(2, 1, Rgb([255, 255, 255]));
+    // Hematoxylin only, with an optical density of 0.5
+    image.put_pixel(1, 0, Rgb([121, 113, 183]));
+    let stains = match utils::color_deconvolve(&image, utils::HE_STAIN_MATRIX) {
+        Ok(val) => val,
+        Err(msg) => panic!("Color deconvolution error:\n{}", msg),
+    };
+    for stain in stains.iter() {
+        assert_eq!(255, stain.get_pixel(0, 0).data[0]);
+    }
+    // 255 * 10^-0.5 = 80.6
+    assert!((i32::from(stains[0].get_pixel(1, 0).data[0]) - 81).abs() <= 2);
+    assert!(stains[1].get_pixel(1, 0).data[0] >= 250);
+    assert!(stains[2].get_pixel(1, 0).data[0] >= 250);
+
+    let singular = [[1.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+    assert!(utils::color_deconvolve(&image, singular).is_err())
+}
+
+#[test]
+fn test_integer_downsample() {
+    assert_eq!(Some(1), utils::integer_downsample((2220, 2967), (2220, 2967)));
+    assert_eq!(Some(4), utils::integer_downsample((46000, 32914), (11500, 8228)));
+    // Rounded up
+    assert_eq!(Some(4), utils::integer_downsample((46000, 32914), (11500, 8229)));
+    assert_eq!(None, utils::integer_downsample((46000, 32914), (11500, 8230)));
+    assert_eq!(None, utils::integer_downsample((1000, 1000), (300, 300)));
+    assert_eq!(None, utils::integer_downsample((1000, 1000), (0, 0)))
+}
+
+#[test]
+fn test_gamma() {
+    let lut = match utils::gamma_lut(2.0) {
+        Ok(val) => val,
+        Err(msg) => panic!("Gamma lookup table error:\n{}", msg),
+    };
+    // 255 * 0.5^2 = 63.75
+    assert_eq!((0, 64, 255), (lut[0], lut[128], lut[255]));
+    let identity = utils::gamma_lut(1.0).unwrap();
+    assert!(identity.iter().enumerate().all(|(value, &entry)| value == entry as usize));
+    assert!(utils::gamma_lut(0.0).is_err());
+    assert!(utils::gamma_lut(f32::NAN).is_err());
+
+    let mut image = RgbaImage::from_pixel(1, 1, Rgba([128, 255, 0, 128]));
+    if let Err(msg) = utils::apply_gamma(&mut image, 2.0) {
+        panic!("Apply gamma error:\n{}", msg);
+    }
+    assert_eq!([64, 255, 0, 128], image.get_pixel(0, 0).data)
+}
+
+#[test]
+fn test_decode_buffer_unpremultiply() {
+    // Expected values are those of the OpenSlide Python bindings, `255 * value / alpha`
+    // rounded down
+    let buffer = vec![0x8040_6401u32, 0x0101_0000, 0x10ff_0008];
+    let word_repr = utils::WordRepresentation::Native;
+    let value = match utils::decode_buffer(&buffer, 1u32, 3u32, word_repr) {
+        Ok(val) => val,
+        Err(msg) => panic!("Decode buffer error:\n{}", msg),
+    };
+    assert_eq!([127, 199, 1, 128], value.get_pixel(0, 0).data);
+    assert_eq!([255, 0, 0, 1], value.get_pixel(1, 0).data);
+    // Channels above alpha are clamped
+    assert_eq!([255, 0, 127, 16], value.get_pixel(2, 0).data)
+}
+
+#[test]
+fn test_decode_buffer_word_representation() {
+    let expected = [
+        Rgba([1, 2, 3, 255]),
+        Rgba([127, 63, 31, 128]),
+        Rgba([0, 0, 0, 0]),
+        Rgba([10, 11, 12, 255]),
+    ];
+    // [alpha, red, green, blue] from the most significant bit
+    let big_endian = vec![0xff01_0203u32, 0x8040_2010, 0x0000_0000, 0xff0a_0b0c];
+    // [blue, green, red, alpha] from the most significant bit
+    let little_endian = vec![0x0302_01ffu32, 0x1020_4080, 0x0000_0000, 0x0c0b_0aff];
+    for (buffer, word_repr) in [
+        (big_endian, utils::WordRepresentation::BigEndian),
+        (little_endian, utils::WordRepresentation::LittleEndian),
+    ]
+    .iter()
+    {
+        let value = match utils::decode_buffer(buffer, 2u32, 2u32, *word_repr) {
+            Ok(val) => val,
+            Err(msg) => panic!("Decode buffer error:\n{}", msg),
+        };
+        assert_eq!((2, 2), value.dimensions());
+        let pixels: Vec<_> = value.pixels().cloned().collect();
+        assert_eq!(expected.to_vec(), pixels, "{:?}", word_repr);
+    }
+}
+
+#[test]
+fn test_decode_buffer_luma() {
+    let buffer = vec![0xffff_0000u32, 0xff00_ff00, 0x8000_0080, 0x0000_0000];
+    let word_repr = utils::WordRepresentation::Native;
+    let value = match utils::decode_buffer_luma(&buffer, 2u32, 2u32, word_repr) {
+        Ok(val) => val,
+        Err(msg) => panic!("Decode buffer error:\n{}", msg),
+    };
+    assert_eq!((2, 2), value.dimensions());
+    // 0.299 * 255 = 76.2, 0.587 * 255 = 149.7, and 0.114 * 255 = 29.1 for the un-premultiplied
+    // blue
+    assert_eq!(vec![76, 150, 29, 0], value.into_raw());
+    assert!(utils::decode_buffer_luma(&buffer, 3u32, 2u32, word_repr).is_err())
+}
+
+#[test]
+fn test_decode_buffer_rgb_vec() {
+    let buffer = vec![0xff01_0203u32, 0x8040_2000, 0x0000_0000];
+    let word_repr = utils::WordRepresentation::Native;
+    let value = match utils::decode_buffer_rgb_vec(&buffer, 1u32, 3u32, word_repr, [255, 0, 200]) {
+        Ok(val) => val,
+        Err(msg) => panic!("Decode buffer error:\n{}", msg),
+    };
+    // Half transparent: 64 + 255 * 127 / 255 = 191, 32 + 0, 0 + 200 * 127 / 255 = 99.6
+    assert_eq!(vec![1, 2, 3, 191, 32, 100, 255, 0, 200], value);
+    assert!(utils::decode_buffer_rgb_vec(&buffer, 2u32, 2u32, word_repr, [0, 0, 0]).is_err())
+}
+
+#[test]
+fn test_orient() {
+    // 3 columns and 2 rows, with distinct red values
+    let image = RgbaImage::from_fn(3, 2, |col, row| Rgba([(10 * row + col) as u8, 0, 0, 255]));
+    let red = |image: &RgbaImage| -> Vec<u8> {
+        image.pixels().map(|pixel| pixel.data[0]).collect()
+    };
+    let cases = [
+        (1, (3, 2), vec![0, 1, 2, 10, 11, 12]),
+        (2, (3, 2), vec![2, 1, 0, 12, 11, 10]),
+        (3, (3, 2), vec![12, 11, 10, 2, 1, 0]),
+        (4, (3, 2), vec![10, 11, 12, 0, 1, 2]),
+        (5, (2, 3), vec![0, 10, 1, 11, 2, 12]),
+        (6, (2, 3), vec![10, 0, 11, 1, 12, 2]),
+        (7, (2, 3), vec![12, 2, 11, 1, 10, 0]),
+        (8, (2, 3), vec![2, 12, 1, 11, 0, 10]),
+    ];
+    for (tag, dimensions, pixels) in cases.iter() {
+        let orientation = match utils::Orientation::from_tiff_tag(*tag) {
+            Some(val) => val,
+            None => panic!("Invalid orientation tag {}", tag),
+        };
+        let value = utils::orient(&image, orientation);
+        assert_eq!(*dimensions, value.dimensions(), "{:?}", orientation);
+        assert_eq!(*pixels, red(&value), "{:?}", orientation);
+        assert_eq!(*dimensions == (2, 3), orientation.swaps_dimensions());
+    }
+    assert_eq!(None, utils::Orientation::from_tiff_tag(0));
+    assert_eq!(None, utils::Orientation::from_tiff_tag(9))
+}
+
+#[cfg(feature = "tensor")]
+#[test]
+fn test_decode_buffer_tensor() {
+    let buffer = vec![0xff33_6699u32, 0x8040_2000];
+    let word_repr = utils::WordRepresentation::Native;
+    let layout = utils::TensorLayout::Nchw;
+    let (value, shape) =
+        match utils::decode_buffer_tensor(&buffer, 1u32, 2u32, word_repr, layout, None) {
+            Ok(val) => val,
+            Err(msg) => panic!("Decode buffer error:\n{}", msg),
+        };
+    assert_eq!([1, 3, 1, 2], shape);
+    assert_eq!(vec![0.2, 127.0 / 255.0, 0.4, 63.0 / 255.0, 0.6, 0.0], value);
+
+    let layout = utils::TensorLayout::Nhwc;
+    let normalize = Some((0.5, 0.25));
+    let (value, shape) =
+        match utils::decode_buffer_tensor(&buffer, 1u32, 2u32, word_repr, layout, normalize) {
+            Ok(val) => val,
+            Err(msg) => panic!("Decode buffer error:\n{}", msg),
+        };
+    assert_eq!([1, 1, 2, 3], shape);
+    assert!((value[0] - (0.2 - 0.5) / 0.25).abs() < 1e-6);
+    assert!((value[5] - (0.0 - 0.5) / 0.25).abs() < 1e-6);
+    let normalize = Some((0.0, 0.0));
+    assert!(utils::decode_buffer_tensor(&buffer, 1u32, 2u32, word_repr, layout, normalize).is_err())
+}